@@ -0,0 +1,124 @@
+//! Compares polling the condition through a `dyn Fn` against the crate's generic polling loop, and
+//! yielding to the runtime between evaluations against spinning.
+
+use core::{
+    future::Future,
+    pin::Pin,
+    sync::atomic::{AtomicBool, AtomicU32, Ordering},
+    task::{Context, Poll},
+    time::Duration,
+};
+
+use std::sync::Arc;
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+
+const POLLS: u32 = 1_000;
+
+/// The trait object based future the crate used before being made generic.
+struct DynFut<'a>(&'a dyn Fn() -> bool);
+
+impl<'a> Future for DynFut<'a> {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        if self.0() {
+            Poll::Ready(())
+        } else {
+            cx.waker().wake_by_ref();
+            Poll::Pending
+        }
+    }
+}
+
+fn poll(c: &mut Criterion) {
+    // A current-thread runtime would turn the spin sleeper's spins into yields.
+    let runtime = tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(1)
+        .enable_time()
+        .build()
+        .unwrap();
+
+    let mut group = c.benchmark_group("poll");
+
+    group.bench_function("dyn", |b| {
+        b.iter(|| {
+            let polls = AtomicU32::new(0);
+            let condition = || black_box(polls.fetch_add(1, Ordering::Relaxed)) == POLLS;
+            runtime.block_on(async {
+                tokio::time::timeout(Duration::from_secs(10), DynFut(&condition))
+                    .await
+                    .unwrap()
+            });
+        })
+    });
+
+    group.bench_function("generic", |b| {
+        b.iter(|| {
+            let polls = AtomicU32::new(0);
+            let condition = || black_box(polls.fetch_add(1, Ordering::Relaxed)) == POLLS;
+            runtime.block_on(async {
+                deadline::deadline_inner_with_interval(
+                    &deadline::RuntimeSleeper,
+                    Duration::from_secs(10),
+                    Duration::ZERO,
+                    condition,
+                )
+                .await
+                .unwrap()
+            });
+        })
+    });
+
+    group.bench_function("spin", |b| {
+        b.iter(|| {
+            let polls = AtomicU32::new(0);
+            let condition = || black_box(polls.fetch_add(1, Ordering::Relaxed)) == POLLS;
+            runtime.block_on(async {
+                deadline::deadline_inner_with_interval(
+                    &deadline::SpinSleeper,
+                    Duration::from_secs(10),
+                    Duration::ZERO,
+                    condition,
+                )
+                .await
+                .unwrap()
+            });
+        })
+    });
+
+    // How soon a condition set by another task is seen.
+    for (name, spin) in [("latency/yield", false), ("latency/spin", true)] {
+        group.bench_function(name, |b| {
+            b.iter(|| {
+                let ready = Arc::new(AtomicBool::new(false));
+                let ready_clone = ready.clone();
+                let condition = move || ready_clone.load(Ordering::Relaxed);
+                runtime.block_on(async {
+                    tokio::spawn(async move {
+                        for _ in 0..POLLS {
+                            tokio::task::yield_now().await;
+                        }
+                        ready.store(true, Ordering::Relaxed);
+                    });
+                    let deadline = deadline::Deadline::new(Duration::from_secs(10))
+                        .poll_interval(Duration::ZERO);
+                    if spin {
+                        deadline
+                            .sleeper(deadline::SpinSleeper)
+                            .wait(condition)
+                            .await
+                    } else {
+                        deadline.wait(condition).await
+                    }
+                    .unwrap()
+                });
+            })
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, poll);
+criterion_main!(benches);
@@ -0,0 +1,91 @@
+//! A time budget shared by the deadlines of a multi-step helper.
+
+use core::time::Duration;
+#[cfg(not(feature = "wasm"))]
+use std::time::Instant;
+
+#[cfg(feature = "wasm")]
+use web_time::Instant;
+
+use crate::{
+    rt,
+    scale::{scale, unscale},
+};
+
+/// A total wait limit shared by several deadlines, each waiting at most for what's left of it.
+///
+/// This bounds a helper made of several steps as a whole, without working out what's left of its
+/// limit before each step: [`deadline_budget!`](crate::deadline_budget) waits for up to the
+/// [`remaining`](Self::remaining) time, and fails straight away once there's none left. The budget
+/// is only a point in time, so it's `Copy`, and can be passed down to nested helpers.
+///
+/// The total passed to [`new`](Self::new) is scaled by `DEADLINE_SCALE` like the macros' wait
+/// limits, and the remaining time is scaled back, so each deadline drawing from the budget ends
+/// with it however the limits are scaled.
+///
+/// # Examples
+///
+/// ```rust
+/// # #[tokio::main]
+/// # async fn main() {
+/// #     use std::{
+/// #         sync::{
+/// #             atomic::{AtomicBool, Ordering},
+/// #             Arc,
+/// #         },
+/// #         time::Duration,
+/// #     };
+/// #
+/// #     use deadline::{deadline_budget, Budget};
+/// async fn start_and_connect(budget: Budget, started: &AtomicBool, connected: &AtomicBool) {
+///     deadline_budget!(budget, || started.load(Ordering::Relaxed), "not started");
+///     deadline_budget!(budget, || connected.load(Ordering::Relaxed), "not connected");
+/// }
+///
+/// let started = Arc::new(AtomicBool::new(false));
+/// let connected = Arc::new(AtomicBool::new(false));
+///
+/// let (started_clone, connected_clone) = (started.clone(), connected.clone());
+/// tokio::spawn(async move {
+///     tokio::time::sleep(Duration::from_millis(5)).await;
+///     started_clone.store(true, Ordering::Relaxed);
+///     tokio::time::sleep(Duration::from_millis(5)).await;
+///     connected_clone.store(true, Ordering::Relaxed);
+/// });
+///
+/// start_and_connect(Budget::new(Duration::from_secs(1)), &started, &connected).await;
+/// # }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Budget {
+    /// When the budget is exhausted, or `None` if that's too far in the future to represent.
+    ends: Option<Instant>,
+}
+
+impl Budget {
+    /// Creates a budget of `total` from now, scaled by `DEADLINE_SCALE`.
+    pub fn new(total: Duration) -> Self {
+        Self {
+            ends: rt::now().checked_add(scale(total)),
+        }
+    }
+
+    /// Creates a budget lasting until `ends`, which isn't scaled.
+    pub fn until(ends: Instant) -> Self {
+        Self { ends: Some(ends) }
+    }
+
+    /// Returns the wait limit left for a deadline drawing from the budget, zero once it's
+    /// exhausted.
+    pub fn remaining(&self) -> Duration {
+        match self.ends {
+            Some(ends) => unscale(ends.saturating_duration_since(rt::now())),
+            None => Duration::MAX,
+        }
+    }
+
+    /// Returns whether there's no time left in the budget.
+    pub fn is_exhausted(&self) -> bool {
+        self.ends.is_some_and(|ends| rt::now() >= ends)
+    }
+}
@@ -0,0 +1,971 @@
+//! A builder composing the polling options the macros expose separately.
+
+use core::{
+    fmt::Debug,
+    iter,
+    sync::atomic::{AtomicU64, Ordering},
+    time::Duration,
+};
+#[cfg(feature = "tokio-util")]
+use core::{
+    future::{poll_fn, Future},
+    pin::pin,
+    task::Poll,
+};
+use std::collections::VecDeque;
+#[cfg(not(feature = "wasm"))]
+use std::time::Instant;
+
+#[cfg(feature = "tokio-util")]
+use tokio_util::sync::CancellationToken;
+#[cfg(feature = "wasm")]
+use web_time::Instant;
+
+use crate::sleeper::AlignedSleeper;
+#[cfg(feature = "tokio")]
+use crate::sleeper::IntervalSleeper;
+use crate::{
+    backoff, error::BoxError, failure::warn_only_timeout, forced_timeout, poll_at_least,
+    scale::scale, DeadlineError, IsReady, RuntimeSleeper, Sleeper, DEFAULT_POLL_INTERVAL,
+};
+
+/// How long to sleep between evaluations of the condition.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Strategy {
+    Interval(Duration),
+    Backoff {
+        start: Duration,
+        cap: Duration,
+    },
+    #[cfg(feature = "tokio")]
+    Steady(Duration),
+    Aligned {
+        anchor: Instant,
+        period: Duration,
+    },
+}
+
+/// How a [`Deadline`] was met.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct DeadlineOutcome {
+    /// How many times the condition was evaluated, including the evaluation that returned `true`.
+    ///
+    /// This is counted the same way whatever the strategy, so it can be asserted on to check how
+    /// quickly something became ready, independently of [`Deadline::max_polls`].
+    pub polls: u64,
+    /// How long it took for the condition to be met.
+    pub elapsed: Duration,
+    /// Whether the deadline elapsed before the condition was met, only ever `true` for a
+    /// [`Deadline::warn_only`].
+    pub timed_out: bool,
+}
+
+/// A deadline for a condition, configured through chained method calls.
+///
+/// This is the typed counterpart to the macros: it waits for the condition the same way, but
+/// combines any of the options and returns a [`DeadlineError`] instead of panicking. The wait limit
+/// is scaled by `DEADLINE_SCALE` like the macros'.
+///
+/// # Examples
+///
+/// ```rust
+/// # #[tokio::main]
+/// # async fn main() {
+/// #     use std::{
+/// #         sync::{
+/// #             atomic::{AtomicI32, Ordering},
+/// #             Arc,
+/// #         },
+/// #         time::Duration,
+/// #     };
+/// #
+/// #     use deadline::Deadline;
+/// let x = Arc::new(AtomicI32::new(41));
+/// let y = 42;
+///
+/// let x_clone = x.clone();
+/// tokio::spawn(async move {
+///     tokio::time::sleep(Duration::from_millis(5)).await;
+///     x_clone.fetch_add(1, Ordering::SeqCst);
+/// });
+///
+/// Deadline::new(Duration::from_millis(100))
+///     .backoff(Duration::from_millis(1), Duration::from_millis(10))
+///     .message("x never reached 42")
+///     .wait(move || x.load(Ordering::Relaxed) == y)
+///     .await
+///     .unwrap();
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+#[must_use = "a deadline does nothing until `wait` is awaited"]
+pub struct Deadline<S = RuntimeSleeper, H = (), D = ()> {
+    wait_limit: Duration,
+    strategy: Strategy,
+    min_polls: u64,
+    max_polls: Option<u64>,
+    message: Option<String>,
+    warn_threshold: Option<f64>,
+    warmup: Duration,
+    jitter: Duration,
+    check_first: bool,
+    back_pressure: Option<(Duration, f64)>,
+    warn_only: bool,
+    #[cfg(feature = "tokio-util")]
+    cancellation: Option<CancellationToken>,
+    sleeper: S,
+    history: usize,
+    snapshot: H,
+    on_timeout: D,
+}
+
+impl Deadline {
+    /// Creates a deadline of `wait_limit`, evaluating the condition every
+    /// [`DEFAULT_POLL_INTERVAL`].
+    pub fn new(wait_limit: Duration) -> Self {
+        Self {
+            wait_limit,
+            strategy: Strategy::Interval(DEFAULT_POLL_INTERVAL),
+            min_polls: 0,
+            max_polls: None,
+            message: None,
+            warn_threshold: None,
+            warmup: Duration::ZERO,
+            jitter: Duration::ZERO,
+            check_first: true,
+            back_pressure: None,
+            warn_only: false,
+            #[cfg(feature = "tokio-util")]
+            cancellation: None,
+            sleeper: RuntimeSleeper,
+            history: 0,
+            snapshot: (),
+            on_timeout: (),
+        }
+    }
+}
+
+impl<S: Sleeper, H: Snapshot, D: OnTimeout> Deadline<S, H, D> {
+    /// Sleeps for `poll_interval` between evaluations, replacing any backoff.
+    pub fn poll_interval(mut self, poll_interval: Duration) -> Self {
+        self.strategy = Strategy::Interval(poll_interval);
+        self
+    }
+
+    /// Doubles the interval between evaluations from `start` up to `cap`, replacing any poll
+    /// interval.
+    pub fn backoff(mut self, start: Duration, cap: Duration) -> Self {
+        self.strategy = Strategy::Backoff { start, cap };
+        self
+    }
+
+    /// Evaluates the condition at a steady cadence of `period`, driven by a
+    /// [`tokio::time::Interval`], replacing any poll interval or backoff. Requires the `tokio`
+    /// feature.
+    ///
+    /// Unlike [`poll_interval`](Self::poll_interval), the time spent evaluating the condition
+    /// counts towards the period, so the evaluations don't drift. An evaluation outlasting the
+    /// period delays the next tick instead of causing a burst of catch-up evaluations. The ticks
+    /// are always those of the tokio runtime, whatever the [`sleeper`](Self::sleeper), and aren't
+    /// shifted by any jitter.
+    #[cfg(feature = "tokio")]
+    pub fn interval_steady(mut self, period: Duration) -> Self {
+        self.strategy = Strategy::Steady(period);
+        self
+    }
+
+    /// Evaluates the condition on a fixed schedule of `anchor + n * period`, replacing any poll
+    /// interval or backoff.
+    ///
+    /// Each sleep lasts until the next instant of the schedule rather than for a period from the
+    /// end of the last evaluation, so evaluations land at predictable instants however long each
+    /// takes. Instants that have already passed, e.g. during a slow evaluation, are skipped rather
+    /// than caught up on. The anchor is on the clock of the [`sleeper`](Self::sleeper), e.g.
+    /// `Instant::now()` for the default one, and the schedule isn't shifted by any jitter. With an
+    /// anchor in the future, the first evaluation still happens straight away unless
+    /// [`check_first`](Self::check_first) is `false`, and the next one at the anchor.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// #     use std::time::{Duration, Instant};
+    /// #
+    /// #     use deadline::Deadline;
+    /// let anchor = Instant::now();
+    /// let x = 42;
+    ///
+    /// Deadline::new(Duration::from_millis(100))
+    ///     .interval_aligned(anchor, Duration::from_millis(10))
+    ///     .wait(move || x == 42)
+    ///     .await
+    ///     .unwrap();
+    /// # }
+    /// ```
+    pub fn interval_aligned(mut self, anchor: Instant, period: Duration) -> Self {
+        self.strategy = Strategy::Aligned { anchor, period };
+        self
+    }
+
+    /// Evaluates the condition at least `min_polls` times before timing out, unless it's met
+    /// earlier.
+    ///
+    /// The intervals between evaluations are shortened as needed to fit them within the wait
+    /// limit, so a cheap condition on a short deadline gets checked more than once. If the wait
+    /// limit still elapses first, e.g. because a coarse timer overslept, the missing evaluations
+    /// are made back to back before giving up. A [`max_polls`](Self::max_polls) below `min_polls`
+    /// takes precedence. The error reports the requested count alongside the actual one.
+    pub fn min_polls(mut self, min_polls: u64) -> Self {
+        self.min_polls = min_polls;
+        self
+    }
+
+    /// Gives up after evaluating the condition `max_polls` times, even if the wait limit hasn't
+    /// elapsed. The condition is always evaluated at least once.
+    pub fn max_polls(mut self, max_polls: u64) -> Self {
+        self.max_polls = Some(max_polls);
+        self
+    }
+
+    /// Sets a message describing the condition, included in the error.
+    pub fn message(mut self, message: impl Into<String>) -> Self {
+        self.message = Some(message.into());
+        self
+    }
+
+    /// Warns on stderr when the condition is met, but only after more than `fraction` of the wait
+    /// limit, e.g. `0.8` for 80%.
+    ///
+    /// A deadline passing this close to its limit is likely to become flaky, and the warning names
+    /// the condition by its message along with the margin left. Nothing is checked by default.
+    pub fn warn_threshold(mut self, fraction: f64) -> Self {
+        self.warn_threshold = Some(fraction);
+        self
+    }
+
+    /// Sleeps for `warmup` before the first evaluation of the condition, e.g. when it's known to be
+    /// false during an initial setup window.
+    ///
+    /// The warmup counts against the wait limit: if it's longer, the condition is evaluated once
+    /// the wait limit has elapsed.
+    pub fn warmup(mut self, warmup: Duration) -> Self {
+        self.warmup = warmup;
+        self
+    }
+
+    /// Randomly shifts each interval between evaluations by up to `jitter` either way, so that many
+    /// deadlines polling in parallel don't synchronize. Requires the `jitter` feature.
+    ///
+    /// As with any interval, a shifted sleep never extends past the wait limit. There's no jitter
+    /// by default, keeping the timings deterministic.
+    #[cfg(feature = "jitter")]
+    pub fn jitter(mut self, jitter: Duration) -> Self {
+        self.jitter = jitter;
+        self
+    }
+
+    /// Evaluates the condition straight away if `check_first` is `true`, the default, or only after
+    /// sleeping for the first interval otherwise, e.g. when it's known to be false at first.
+    ///
+    /// Unlike a [`warmup`](Self::warmup), which is a one-off delay, this only shifts the
+    /// evaluations by one interval of the poll cadence, whichever the strategy.
+    pub fn check_first(mut self, check_first: bool) -> Self {
+        self.check_first = check_first;
+        self
+    }
+
+    /// Warns on stderr when the deadline elapses, returning a [`DeadlineOutcome`] which is
+    /// [`timed_out`](DeadlineOutcome::timed_out) instead of an error.
+    ///
+    /// This is meant for a deadline too flaky to fail the tests yet, to find out how often it
+    /// would. The warnings start with `deadline [warn-only]`, so such deadlines are easily found in
+    /// the output, and are logged as well with the `log` or `tracing` features. Errors other than
+    /// timeouts are still returned.
+    pub fn warn_only(mut self) -> Self {
+        self.warn_only = true;
+        self
+    }
+
+    /// Lengthens the interval after any evaluation of the condition taking longer than `threshold`
+    /// to `ratio` times that evaluation's duration, so that an expensive condition doing real work
+    /// doesn't take the CPU away from the system under test.
+    ///
+    /// The time spent evaluating the condition is then measured, and the error reports it
+    /// alongside the time spent sleeping. Only the intervals of
+    /// [`poll_interval`](Self::poll_interval) and [`backoff`](Self::backoff) are lengthened, and
+    /// never past the wait limit. Evaluations aren't measured by default.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// #     use std::time::Duration;
+    /// #
+    /// #     use deadline::Deadline;
+    /// let error = Deadline::new(Duration::from_millis(20))
+    ///     .back_pressure(Duration::from_millis(1), 2.0)
+    ///     .wait(|| {
+    ///         std::thread::sleep(Duration::from_millis(2));
+    ///         false
+    ///     })
+    ///     .await
+    ///     .unwrap_err();
+    ///
+    /// assert!(error.evaluating().unwrap() >= Duration::from_millis(2));
+    /// # }
+    /// ```
+    pub fn back_pressure(mut self, threshold: Duration, ratio: f64) -> Self {
+        self.back_pressure = Some((threshold, ratio));
+        self
+    }
+
+    /// Stops waiting once `token` is cancelled, returning a [`DeadlineError::Cancelled`]. Requires
+    /// the `tokio-util` feature.
+    ///
+    /// This lets a test tear down a wait that's become pointless, e.g. because a sibling task
+    /// failed. The condition is still evaluated once, so one that's already met isn't reported as
+    /// cancelled.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// #     use std::time::Duration;
+    /// #
+    /// #     use deadline::Deadline;
+    /// #     use tokio_util::sync::CancellationToken;
+    /// let token = CancellationToken::new();
+    ///
+    /// let token_clone = token.clone();
+    /// tokio::spawn(async move {
+    ///     tokio::time::sleep(Duration::from_millis(5)).await;
+    ///     token_clone.cancel();
+    /// });
+    ///
+    /// let error = Deadline::new(Duration::from_secs(10))
+    ///     .cancel_on(token)
+    ///     .wait(|| false)
+    ///     .await
+    ///     .unwrap_err();
+    ///
+    /// assert!(error.is_cancelled());
+    /// # }
+    /// ```
+    #[cfg(feature = "tokio-util")]
+    pub fn cancel_on(mut self, token: CancellationToken) -> Self {
+        self.cancellation = Some(token);
+        self
+    }
+
+    /// Sleeps on `sleeper` instead of the runtime's timers.
+    pub fn sleeper<T: Sleeper>(self, sleeper: T) -> Deadline<T, H, D> {
+        Deadline {
+            wait_limit: self.wait_limit,
+            strategy: self.strategy,
+            min_polls: self.min_polls,
+            max_polls: self.max_polls,
+            message: self.message,
+            warn_threshold: self.warn_threshold,
+            warmup: self.warmup,
+            jitter: self.jitter,
+            check_first: self.check_first,
+            back_pressure: self.back_pressure,
+            warn_only: self.warn_only,
+            #[cfg(feature = "tokio-util")]
+            cancellation: self.cancellation,
+            sleeper,
+            history: self.history,
+            snapshot: self.snapshot,
+            on_timeout: self.on_timeout,
+        }
+    }
+
+    /// Records a snapshot of the state the condition looks at, taken with `snapshot` after each
+    /// evaluation returning `false`, and includes the last `len` of them in the error.
+    ///
+    /// This turns an intermittent timeout into a trace of what was observed along the way, e.g.
+    /// `observed: [..., 41, 41, 41]`. Older snapshots are dropped to bound the memory used. Nothing
+    /// is recorded by default.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// #     use std::{cell::Cell, time::Duration};
+    /// #
+    /// #     use deadline::Deadline;
+    /// let x = Cell::new(41);
+    ///
+    /// let error = Deadline::new(Duration::from_millis(10))
+    ///     .record_history(3, || x.get())
+    ///     .wait(|| x.get() == 42)
+    ///     .await
+    ///     .unwrap_err();
+    ///
+    /// assert_eq!(error.observed(), ["41", "41", "41"]);
+    /// # }
+    /// ```
+    pub fn record_history<T: Debug, F: FnMut() -> T>(
+        self,
+        len: usize,
+        snapshot: F,
+    ) -> Deadline<S, F, D> {
+        Deadline {
+            wait_limit: self.wait_limit,
+            strategy: self.strategy,
+            min_polls: self.min_polls,
+            max_polls: self.max_polls,
+            message: self.message,
+            warn_threshold: self.warn_threshold,
+            warmup: self.warmup,
+            jitter: self.jitter,
+            check_first: self.check_first,
+            back_pressure: self.back_pressure,
+            warn_only: self.warn_only,
+            #[cfg(feature = "tokio-util")]
+            cancellation: self.cancellation,
+            sleeper: self.sleeper,
+            history: len,
+            snapshot,
+            on_timeout: self.on_timeout,
+        }
+    }
+
+    /// Calls `on_timeout` when the condition isn't met in time, before returning the error, e.g. to
+    /// print or collect context the condition doesn't look at, like server logs or metrics.
+    ///
+    /// The closure can return a `String` of diagnostics, which the error includes and displays
+    /// after its description, or `()` for side effects only. It's only called on failure, once,
+    /// never when the condition is met.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// #     use std::{cell::Cell, time::Duration};
+    /// #
+    /// #     use deadline::Deadline;
+    /// let x = Cell::new(41);
+    ///
+    /// let error = Deadline::new(Duration::from_millis(10))
+    ///     .on_timeout(|| format!("last requests: {:?}", ["GET /ready"]))
+    ///     .wait(|| x.get() == 42)
+    ///     .await
+    ///     .unwrap_err();
+    ///
+    /// assert_eq!(error.diagnostics(), Some(r#"last requests: ["GET /ready"]"#));
+    /// # }
+    /// ```
+    pub fn on_timeout<T: Diagnostics, F: FnOnce() -> T>(self, on_timeout: F) -> Deadline<S, H, F> {
+        Deadline {
+            wait_limit: self.wait_limit,
+            strategy: self.strategy,
+            min_polls: self.min_polls,
+            max_polls: self.max_polls,
+            message: self.message,
+            warn_threshold: self.warn_threshold,
+            warmup: self.warmup,
+            jitter: self.jitter,
+            check_first: self.check_first,
+            back_pressure: self.back_pressure,
+            warn_only: self.warn_only,
+            #[cfg(feature = "tokio-util")]
+            cancellation: self.cancellation,
+            sleeper: self.sleeper,
+            history: self.history,
+            snapshot: self.snapshot,
+            on_timeout,
+        }
+    }
+
+    /// Waits for the condition to return `true`, reporting how many evaluations and how long it
+    /// took.
+    ///
+    /// The condition is an `FnMut`, so it can accumulate state across evaluations.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// #     use std::time::Duration;
+    /// #
+    /// #     use deadline::Deadline;
+    /// let mut calls = 0;
+    ///
+    /// let outcome = Deadline::new(Duration::from_millis(100))
+    ///     .wait(|| {
+    ///         calls += 1;
+    ///         calls == 2
+    ///     })
+    ///     .await
+    ///     .unwrap();
+    ///
+    /// assert!(outcome.polls < 3);
+    /// # }
+    /// ```
+    pub async fn wait<R: IsReady, F: FnMut() -> R>(
+        self,
+        mut condition: F,
+    ) -> Result<DeadlineOutcome, DeadlineError> {
+        self.poll(|| Ok(condition().is_ready())).await
+    }
+
+    /// Like [`wait`](Self::wait), but for a condition which can fail with an error, waiting no
+    /// longer once it does.
+    ///
+    /// `Ok(true)` means the condition is met and `Ok(false)` that it's worth waiting for, while an
+    /// `Err` is returned straight away as a [`DeadlineError::Terminal`], e.g. when the process
+    /// expected to become ready has exited.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// #     use std::time::Duration;
+    /// #
+    /// #     use deadline::{Deadline, DeadlineError};
+    /// let error = Deadline::new(Duration::from_secs(10))
+    ///     .try_wait(|| Err::<bool, _>("the server exited with status 1"))
+    ///     .await
+    ///     .unwrap_err();
+    ///
+    /// assert!(error.is_terminal());
+    /// assert_eq!(error.polls(), 1);
+    ///
+    /// let DeadlineError::Terminal { error, .. } = error else {
+    ///     unreachable!()
+    /// };
+    /// assert_eq!(error.to_string(), "the server exited with status 1");
+    /// # }
+    /// ```
+    pub async fn try_wait<E: Into<BoxError>, F: FnMut() -> Result<bool, E>>(
+        self,
+        mut condition: F,
+    ) -> Result<DeadlineOutcome, DeadlineError> {
+        self.poll(|| condition().map_err(Into::into)).await
+    }
+
+    /// Polls the condition, which is met once it returns `Ok(true)` and fails if it returns an
+    /// error.
+    async fn poll(
+        mut self,
+        mut condition: impl FnMut() -> Result<bool, BoxError>,
+    ) -> Result<DeadlineOutcome, DeadlineError> {
+        let wait_limit = scale(self.wait_limit);
+        if let Err(error) = forced_timeout(wait_limit) {
+            return warned(
+                self.warn_only,
+                error
+                    .with_message(self.message)
+                    .with_diagnostics(self.on_timeout.on_timeout()),
+            );
+        }
+        let started = self.sleeper.now();
+        if !self.warmup.is_zero() {
+            self.sleeper.sleep(self.warmup.min(wait_limit)).await;
+        }
+        let warmed_up = self.sleeper.now().saturating_duration_since(started);
+        let remaining = wait_limit.saturating_sub(warmed_up);
+        let back_pressure = self.back_pressure;
+        // The cost of the last evaluation in nanoseconds, for the intervals to be stretched by.
+        // It's atomic rather than a `Cell` for the future to stay `Send`.
+        let cost = AtomicU64::new(0);
+        let mut evaluating = Duration::ZERO;
+        let adjusted = |interval| {
+            stretched(
+                jittered(interval, self.jitter),
+                Duration::from_nanos(cost.load(Ordering::Relaxed)),
+                back_pressure,
+            )
+        };
+        let mut polls = 0;
+        let mut observed = VecDeque::with_capacity(self.history.min(64));
+        let snapshot = &mut self.snapshot;
+        let history = self.history;
+        let sleeper = &self.sleeper;
+        let condition = || {
+            polls += 1;
+            let evaluated = sleeper.now();
+            let met = condition();
+            if back_pressure.is_some() {
+                let took = sleeper.now().saturating_duration_since(evaluated);
+                let nanos = u64::try_from(took.as_nanos()).unwrap_or(u64::MAX);
+                cost.store(nanos, Ordering::Relaxed);
+                evaluating += took;
+            }
+            match met {
+                Ok(true) => return Some(Ok(())),
+                Ok(false) => {}
+                Err(error) => return Some(Err(error)),
+            }
+            if history > 0 {
+                if observed.len() == history {
+                    observed.pop_front();
+                }
+                observed.extend(snapshot.snapshot());
+            }
+            None
+        };
+
+        let polling = async {
+            match self.strategy {
+                Strategy::Interval(poll_interval) => {
+                    poll_strategy(
+                        &self.sleeper,
+                        remaining,
+                        self.min_polls,
+                        self.max_polls,
+                        self.check_first,
+                        iter::repeat(poll_interval).map(adjusted),
+                        condition,
+                    )
+                    .await
+                }
+                Strategy::Backoff { start, cap } => {
+                    poll_strategy(
+                        &self.sleeper,
+                        remaining,
+                        self.min_polls,
+                        self.max_polls,
+                        self.check_first,
+                        backoff(start, cap).map(adjusted),
+                        condition,
+                    )
+                    .await
+                }
+                #[cfg(feature = "tokio")]
+                Strategy::Steady(period) => {
+                    // Each sleep lasts until the next tick, clamped to the remaining time.
+                    poll_strategy(
+                        &IntervalSleeper::new(period),
+                        remaining,
+                        self.min_polls,
+                        self.max_polls,
+                        self.check_first,
+                        iter::repeat(Duration::MAX),
+                        condition,
+                    )
+                    .await
+                }
+                Strategy::Aligned { anchor, period } => {
+                    poll_strategy(
+                        &AlignedSleeper::new(&self.sleeper, anchor, period),
+                        remaining,
+                        self.min_polls,
+                        self.max_polls,
+                        self.check_first,
+                        iter::repeat(Duration::MAX),
+                        condition,
+                    )
+                    .await
+                }
+            }
+        };
+        #[cfg(feature = "tokio-util")]
+        let result = match &self.cancellation {
+            Some(token) => match cancellable(polling, token).await {
+                Some(result) => result,
+                None => {
+                    let waited = self.sleeper.now().saturating_duration_since(started);
+                    return Err(DeadlineError::cancelled(wait_limit, waited, polls)
+                        .with_message(self.message));
+                }
+            },
+            None => polling.await,
+        };
+        #[cfg(not(feature = "tokio-util"))]
+        let result = polling.await;
+
+        match result {
+            Ok(Ok(())) => {}
+            Ok(Err(error)) => {
+                let waited = self.sleeper.now().saturating_duration_since(started);
+                return Err(DeadlineError::terminal(error, wait_limit, waited, polls)
+                    .with_message(self.message));
+            }
+            Err(error) => {
+                return warned(
+                    self.warn_only,
+                    error
+                        .delayed(warmed_up)
+                        .with_min_polls(self.min_polls)
+                        .with_wait_limit(wait_limit)
+                        .with_message(self.message)
+                        .with_observed(observed.into())
+                        .with_evaluating(back_pressure.map(|_| evaluating))
+                        .with_diagnostics(self.on_timeout.on_timeout()),
+                );
+            }
+        }
+
+        let elapsed = self.sleeper.now().saturating_duration_since(started);
+        if let Some(warning) = close_call(
+            self.message.as_deref(),
+            elapsed,
+            wait_limit,
+            self.warn_threshold,
+        ) {
+            eprintln!("{warning}");
+        }
+
+        Ok(DeadlineOutcome {
+            polls,
+            elapsed,
+            timed_out: false,
+        })
+    }
+}
+
+/// Returns the timeout as an error, or warns about it and returns it as an outcome if
+/// `warn_only`.
+fn warned(warn_only: bool, error: DeadlineError) -> Result<DeadlineOutcome, DeadlineError> {
+    if !warn_only {
+        return Err(error);
+    }
+    warn_only_timeout(&error);
+
+    Ok(DeadlineOutcome {
+        polls: error.polls(),
+        elapsed: error.waited(),
+        timed_out: true,
+    })
+}
+
+/// Polls `future` to completion, unless `token` is cancelled first.
+#[cfg(feature = "tokio-util")]
+async fn cancellable<T>(future: impl Future<Output = T>, token: &CancellationToken) -> Option<T> {
+    let mut future = pin!(future);
+    let mut cancelled = pin!(token.cancelled());
+
+    poll_fn(|cx| {
+        if let Poll::Ready(output) = future.as_mut().poll(cx) {
+            return Poll::Ready(Some(output));
+        }
+        cancelled.as_mut().poll(cx).map(|()| None)
+    })
+    .await
+}
+
+/// Polls the condition per the strategy's intervals, sleeping for the first one beforehand unless
+/// `check_first`.
+async fn poll_strategy<S: Sleeper, T>(
+    sleeper: &S,
+    wait_limit: Duration,
+    min_polls: u64,
+    max_polls: Option<u64>,
+    check_first: bool,
+    mut intervals: impl Iterator<Item = Duration>,
+    condition: impl FnMut() -> Option<T>,
+) -> Result<T, DeadlineError> {
+    let started = sleeper.now();
+    if !check_first {
+        if let Some(interval) = intervals.next() {
+            sleeper.sleep(interval.min(wait_limit)).await;
+        }
+    }
+    let slept = sleeper.now().saturating_duration_since(started);
+
+    poll_at_least(
+        sleeper,
+        wait_limit.saturating_sub(slept),
+        min_polls,
+        max_polls,
+        intervals,
+        condition,
+    )
+    .await
+    .map_err(|error| error.delayed(slept))
+}
+
+/// Takes snapshots for [`Deadline::record_history`], rendered with their `Debug` implementation.
+#[doc(hidden)]
+pub trait Snapshot {
+    /// Returns the current snapshot, or `None` when not recording.
+    fn snapshot(&mut self) -> Option<String>;
+}
+
+impl Snapshot for () {
+    fn snapshot(&mut self) -> Option<String> {
+        None
+    }
+}
+
+impl<T: Debug, F: FnMut() -> T> Snapshot for F {
+    fn snapshot(&mut self) -> Option<String> {
+        Some(format!("{:?}", self()))
+    }
+}
+
+/// Calls the closure passed to [`Deadline::on_timeout`], returning its diagnostics, if any.
+#[doc(hidden)]
+pub trait OnTimeout {
+    fn on_timeout(self) -> Option<String>;
+}
+
+impl OnTimeout for () {
+    fn on_timeout(self) -> Option<String> {
+        None
+    }
+}
+
+impl<T: Diagnostics, F: FnOnce() -> T> OnTimeout for F {
+    fn on_timeout(self) -> Option<String> {
+        self().into_diagnostics()
+    }
+}
+
+/// What a [`Deadline::on_timeout`] closure can return: a `String` of diagnostics, or `()`.
+#[doc(hidden)]
+pub trait Diagnostics {
+    fn into_diagnostics(self) -> Option<String>;
+}
+
+impl Diagnostics for () {
+    fn into_diagnostics(self) -> Option<String> {
+        None
+    }
+}
+
+impl Diagnostics for String {
+    fn into_diagnostics(self) -> Option<String> {
+        Some(self)
+    }
+}
+
+/// Shifts the interval by a random offset within `jitter` either way.
+#[cfg(feature = "jitter")]
+fn jittered(interval: Duration, jitter: Duration) -> Duration {
+    if jitter.is_zero() {
+        return interval;
+    }
+
+    let jitter = u64::try_from(jitter.as_nanos()).unwrap_or(u64::MAX);
+    let offset = fastrand::u64(0..=jitter.saturating_mul(2));
+    interval
+        .saturating_add(Duration::from_nanos(offset))
+        .saturating_sub(Duration::from_nanos(jitter))
+}
+
+#[cfg(not(feature = "jitter"))]
+fn jittered(interval: Duration, _: Duration) -> Duration {
+    interval
+}
+
+/// Lengthens the interval to `ratio` times the cost of the last evaluation, if that exceeded the
+/// threshold of the back pressure.
+fn stretched(
+    interval: Duration,
+    cost: Duration,
+    back_pressure: Option<(Duration, f64)>,
+) -> Duration {
+    match back_pressure {
+        Some((threshold, ratio)) if cost > threshold => {
+            let stretched = (cost.as_secs_f64() * ratio).max(0.0);
+            interval.max(Duration::try_from_secs_f64(stretched).unwrap_or(Duration::MAX))
+        }
+        _ => interval,
+    }
+}
+
+/// Describes a condition met after more than `threshold` of the wait limit.
+fn close_call(
+    message: Option<&str>,
+    waited: Duration,
+    wait_limit: Duration,
+    threshold: Option<f64>,
+) -> Option<String> {
+    let threshold = threshold?;
+    let used = waited.as_secs_f64() / wait_limit.as_secs_f64();
+    if wait_limit.is_zero() || used <= threshold {
+        return None;
+    }
+
+    Some(format!(
+        "deadline: {} was only met after {:?} of {:?} ({:.0}% of the wait limit), leaving {:?}",
+        message.unwrap_or("the condition"),
+        waited,
+        wait_limit,
+        used * 100.0,
+        wait_limit.saturating_sub(waited)
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_only_stretches_intervals_after_evaluations_over_the_threshold() {
+        let interval = Duration::from_millis(5);
+        let back_pressure = Some((Duration::from_millis(10), 1.5));
+
+        assert_eq!(
+            stretched(interval, Duration::from_millis(20), None),
+            interval
+        );
+        assert_eq!(
+            stretched(interval, Duration::from_millis(10), back_pressure),
+            interval
+        );
+        assert_eq!(
+            stretched(interval, Duration::from_millis(20), back_pressure),
+            Duration::from_millis(30)
+        );
+        assert_eq!(
+            stretched(
+                interval,
+                Duration::from_millis(20),
+                Some((Duration::ZERO, -1.0))
+            ),
+            interval
+        );
+    }
+
+    #[cfg(feature = "jitter")]
+    #[test]
+    fn it_keeps_the_jitter_within_bounds() {
+        let interval = Duration::from_millis(10);
+        let jitter = Duration::from_millis(3);
+
+        for _ in 0..100 {
+            let jittered = jittered(interval, jitter);
+            assert!(jittered >= interval - jitter && jittered <= interval + jitter);
+        }
+        assert_eq!(jittered(interval, Duration::ZERO), interval);
+    }
+
+    #[test]
+    fn it_only_warns_above_the_threshold() {
+        let wait_limit = Duration::from_millis(100);
+
+        assert_eq!(
+            close_call(None, Duration::from_millis(90), wait_limit, None),
+            None
+        );
+        assert_eq!(
+            close_call(None, Duration::from_millis(80), wait_limit, Some(0.8)),
+            None
+        );
+        assert_eq!(
+            close_call(
+                Some("x == y"),
+                Duration::from_millis(90),
+                wait_limit,
+                Some(0.8)
+            )
+            .as_deref(),
+            Some(
+                "deadline: x == y was only met after 90ms of 100ms (90% of the wait limit), \
+                 leaving 10ms"
+            )
+        );
+    }
+}
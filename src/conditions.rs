@@ -0,0 +1,41 @@
+//! Groups of conditions waited on together by `deadline_all!` and `deadline_any!`.
+
+/// A tuple of condition closures, evaluated by index.
+#[doc(hidden)]
+pub trait Conditions {
+    /// Returns the number of conditions.
+    fn count(&self) -> usize;
+
+    /// Evaluates the condition at `index`.
+    fn evaluate(&self, index: usize) -> bool;
+}
+
+macro_rules! impl_conditions {
+    ($($index: tt $condition: ident),+) => {
+        impl<$($condition: Fn() -> bool),+> Conditions for ($($condition,)+) {
+            fn count(&self) -> usize {
+                [$($index),+].len()
+            }
+
+            fn evaluate(&self, index: usize) -> bool {
+                match index {
+                    $($index => (self.$index)(),)+
+                    _ => panic!("no condition at index {index}"),
+                }
+            }
+        }
+    };
+}
+
+impl_conditions!(0 A);
+impl_conditions!(0 A, 1 B);
+impl_conditions!(0 A, 1 B, 2 C);
+impl_conditions!(0 A, 1 B, 2 C, 3 D);
+impl_conditions!(0 A, 1 B, 2 C, 3 D, 4 E);
+impl_conditions!(0 A, 1 B, 2 C, 3 D, 4 E, 5 F);
+impl_conditions!(0 A, 1 B, 2 C, 3 D, 4 E, 5 F, 6 G);
+impl_conditions!(0 A, 1 B, 2 C, 3 D, 4 E, 5 F, 6 G, 7 H);
+impl_conditions!(0 A, 1 B, 2 C, 3 D, 4 E, 5 F, 6 G, 7 H, 8 I);
+impl_conditions!(0 A, 1 B, 2 C, 3 D, 4 E, 5 F, 6 G, 7 H, 8 I, 9 J);
+impl_conditions!(0 A, 1 B, 2 C, 3 D, 4 E, 5 F, 6 G, 7 H, 8 I, 9 J, 10 K);
+impl_conditions!(0 A, 1 B, 2 C, 3 D, 4 E, 5 F, 6 G, 7 H, 8 I, 9 J, 10 K, 11 L);
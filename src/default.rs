@@ -0,0 +1,165 @@
+//! The wait limit `deadline_default!` and the poll strategy `deadline!` use, configured once for a
+//! whole test suite.
+
+use core::{
+    sync::atomic::{AtomicU64, Ordering},
+    time::Duration,
+};
+use std::sync::{OnceLock, PoisonError, RwLock};
+
+use crate::{set_debug_scale, PollStrategy, DEFAULT_POLL_INTERVAL};
+
+/// The wait limit [`deadline_default!`](crate::deadline_default) uses when none is configured.
+pub const DEFAULT_WAIT_LIMIT: Duration = Duration::from_secs(5);
+
+/// The wait limit set through [`set_default_wait_limit`], in nanoseconds, or `UNSET`.
+static WAIT_LIMIT: AtomicU64 = AtomicU64::new(UNSET);
+
+const UNSET: u64 = u64::MAX;
+
+/// Sets the wait limit [`deadline_default!`](crate::deadline_default) uses from now on, in place
+/// of the `DEADLINE_DEFAULT_MS` environment variable and [`DEFAULT_WAIT_LIMIT`].
+///
+/// The setting is global to the process, so it's best called once, before the tests using it
+/// run. Wait limits are capped just short of 585 years.
+pub fn set_default_wait_limit(wait_limit: Duration) {
+    let nanos = u64::try_from(wait_limit.as_nanos()).unwrap_or(UNSET - 1);
+    WAIT_LIMIT.store(nanos.min(UNSET - 1), Ordering::Relaxed);
+}
+
+/// Returns the wait limit [`deadline_default!`](crate::deadline_default) uses.
+///
+/// This is the value passed to [`set_default_wait_limit`] if it was called, otherwise the number
+/// of milliseconds in the `DEADLINE_DEFAULT_MS` environment variable if it's set, otherwise
+/// [`DEFAULT_WAIT_LIMIT`]. Like `DEADLINE_SCALE`, the environment variable is read once, on first
+/// use, and an invalid value is ignored with a warning.
+pub fn default_wait_limit() -> Duration {
+    static FROM_ENV: OnceLock<Duration> = OnceLock::new();
+
+    match WAIT_LIMIT.load(Ordering::Relaxed) {
+        UNSET => *FROM_ENV
+            .get_or_init(|| parse_wait_limit(std::env::var("DEADLINE_DEFAULT_MS").ok().as_deref())),
+        nanos => Duration::from_nanos(nanos),
+    }
+}
+
+/// The poll strategy set through [`set_defaults`], if any.
+static POLL_STRATEGY: RwLock<Option<PollStrategy<'static>>> = RwLock::new(None);
+
+/// Process-wide defaults for the macros, applied with [`set_defaults`].
+///
+/// Each default left unset keeps its current value.
+#[derive(Debug, Clone, Copy, Default)]
+#[must_use]
+pub struct Defaults {
+    wait_limit: Option<Duration>,
+    poll_strategy: Option<PollStrategy<'static>>,
+    debug_scale: Option<f64>,
+}
+
+impl Defaults {
+    /// Creates defaults leaving everything as it is.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the wait limit [`deadline_default!`](crate::deadline_default) uses, like
+    /// [`set_default_wait_limit`].
+    pub fn wait_limit(mut self, wait_limit: Duration) -> Self {
+        self.wait_limit = Some(wait_limit);
+        self
+    }
+
+    /// Sets the poll strategy [`deadline!`](crate::deadline) uses, in place of sleeping for
+    /// [`DEFAULT_POLL_INTERVAL`] between evaluations.
+    pub fn poll_strategy(mut self, poll_strategy: PollStrategy<'static>) -> Self {
+        self.poll_strategy = Some(poll_strategy);
+        self
+    }
+
+    /// Sets the factor multiplying every wait limit in debug builds, like [`set_debug_scale`].
+    pub fn debug_scale(mut self, factor: f64) -> Self {
+        self.debug_scale = Some(factor);
+        self
+    }
+}
+
+/// Applies the defaults for the whole process.
+///
+/// The poll strategy is used by [`deadline!`](crate::deadline), along with
+/// [`deadline_default!`](crate::deadline_default), [`deadline_until!`](crate::deadline_until) and
+/// [`deadline_retry_panics!`](crate::deadline_retry_panics), which poll the same way. The macros
+/// picking their own strategy, like [`deadline_with_interval!`](crate::deadline_with_interval),
+/// ignore it. Like [`set_default_wait_limit`], this is best called once, before the tests run: a
+/// change only affects the macros called after it.
+///
+/// # Examples
+///
+/// ```rust
+/// # #[tokio::main]
+/// # async fn main() {
+/// #     use std::time::Duration;
+/// #
+/// #     use deadline::{deadline, Defaults, PollStrategy};
+/// deadline::set_defaults(
+///     Defaults::new()
+///         .wait_limit(Duration::from_secs(1))
+///         .poll_strategy(PollStrategy::Interval(Duration::from_millis(2))),
+/// );
+///
+/// let x = 42;
+/// deadline!(Duration::from_millis(10), move || x == 42);
+/// # }
+/// ```
+pub fn set_defaults(defaults: Defaults) {
+    if let Some(wait_limit) = defaults.wait_limit {
+        set_default_wait_limit(wait_limit);
+    }
+    if let Some(poll_strategy) = defaults.poll_strategy {
+        *POLL_STRATEGY
+            .write()
+            .unwrap_or_else(PoisonError::into_inner) = Some(poll_strategy);
+    }
+    if let Some(factor) = defaults.debug_scale {
+        set_debug_scale(factor);
+    }
+}
+
+/// Returns the poll strategy [`deadline!`](crate::deadline) uses: the one passed to
+/// [`set_defaults`] if any, otherwise sleeping for [`DEFAULT_POLL_INTERVAL`] between evaluations.
+pub fn default_poll_strategy() -> PollStrategy<'static> {
+    POLL_STRATEGY
+        .read()
+        .unwrap_or_else(PoisonError::into_inner)
+        .unwrap_or(PollStrategy::Interval(DEFAULT_POLL_INTERVAL))
+}
+
+fn parse_wait_limit(value: Option<&str>) -> Duration {
+    let Some(value) = value else {
+        return DEFAULT_WAIT_LIMIT;
+    };
+
+    match value.trim().parse::<u64>() {
+        Ok(millis) => Duration::from_millis(millis),
+        Err(_) => {
+            eprintln!(
+                "deadline: ignoring invalid DEADLINE_DEFAULT_MS value {value:?}, expected a \
+                 number of milliseconds"
+            );
+            DEFAULT_WAIT_LIMIT
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_parses_the_wait_limit() {
+        assert_eq!(parse_wait_limit(None), DEFAULT_WAIT_LIMIT);
+        assert_eq!(parse_wait_limit(Some(" 250 ")), Duration::from_millis(250));
+        assert_eq!(parse_wait_limit(Some("5s")), DEFAULT_WAIT_LIMIT);
+        assert_eq!(parse_wait_limit(Some("-1")), DEFAULT_WAIT_LIMIT);
+    }
+}
@@ -0,0 +1,469 @@
+//! Errors returned by the non-panicking parts of the API.
+
+use core::{any::Any, fmt, time::Duration};
+
+/// The error a condition failed with through [`Deadline::try_wait`](crate::Deadline::try_wait),
+/// as held by [`DeadlineError::Terminal`].
+pub type BoxError = Box<dyn std::error::Error + Send + Sync>;
+
+/// Error returned by [`try_deadline!`](crate::try_deadline),
+/// [`deadline_q!`](crate::deadline_q) and [`Deadline::wait`](crate::Deadline::wait) when the
+/// condition isn't met.
+///
+/// Each variant is a different way of not being met, to branch on: the wait limit elapsed, the
+/// polls ran out, the wait was cancelled or the condition failed with an error. Of these,
+/// [`is_timed_out`](Self::is_timed_out) covers the first two.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum DeadlineError {
+    /// The wait limit elapsed before the condition was met.
+    #[non_exhaustive]
+    TimedOut {
+        /// The wait limit the condition was given, after scaling.
+        wait_limit: Duration,
+        /// How long the condition was waited on.
+        waited: Duration,
+        /// How many times the condition was evaluated.
+        polls: u64,
+        /// The minimum number of evaluations set through
+        /// [`Deadline::min_polls`](crate::Deadline::min_polls), or zero.
+        min_polls: u64,
+        /// The message set on the builder, if any.
+        message: Option<Box<str>>,
+        /// The condition waited on, as written in [`try_deadline!`](crate::try_deadline).
+        condition: Option<Box<str>>,
+        /// The last snapshots recorded through
+        /// [`Deadline::record_history`](crate::Deadline::record_history), oldest first.
+        observed: Box<[String]>,
+        /// How long was spent evaluating the condition, the rest of the wait being spent
+        /// sleeping, if measured through
+        /// [`Deadline::back_pressure`](crate::Deadline::back_pressure).
+        evaluating: Option<Duration>,
+        /// The diagnostics returned by the [`Deadline::on_timeout`](crate::Deadline::on_timeout)
+        /// closure, if any.
+        diagnostics: Option<Box<str>>,
+    },
+    /// The condition was evaluated the maximum number of times without being met.
+    #[non_exhaustive]
+    PollsExhausted {
+        /// The wait limit the condition was given, after scaling.
+        wait_limit: Duration,
+        /// How long the condition was waited on.
+        waited: Duration,
+        /// How many times the condition was evaluated.
+        polls: u64,
+        /// The message set on the builder, if any.
+        message: Option<Box<str>>,
+        /// The condition waited on, as written in [`try_deadline!`](crate::try_deadline).
+        condition: Option<Box<str>>,
+        /// The last snapshots recorded through
+        /// [`Deadline::record_history`](crate::Deadline::record_history), oldest first.
+        observed: Box<[String]>,
+        /// How long was spent evaluating the condition, the rest of the wait being spent
+        /// sleeping, if measured through
+        /// [`Deadline::back_pressure`](crate::Deadline::back_pressure).
+        evaluating: Option<Duration>,
+        /// The diagnostics returned by the [`Deadline::on_timeout`](crate::Deadline::on_timeout)
+        /// closure, if any.
+        diagnostics: Option<Box<str>>,
+    },
+    /// The wait was cancelled through
+    /// [`Deadline::cancel_on`](crate::Deadline::cancel_on) before the condition was met.
+    #[non_exhaustive]
+    Cancelled {
+        /// The wait limit the condition was given, after scaling.
+        wait_limit: Duration,
+        /// How long the condition was waited on.
+        waited: Duration,
+        /// How many times the condition was evaluated.
+        polls: u64,
+        /// The message set on the builder, if any.
+        message: Option<Box<str>>,
+        /// The condition waited on.
+        condition: Option<Box<str>>,
+    },
+    /// The condition passed to [`Deadline::try_wait`](crate::Deadline::try_wait) failed with an
+    /// error, which is included in the `Display` output and returned as the error's `source`.
+    #[non_exhaustive]
+    Terminal {
+        /// The error the condition failed with, converted into a [`BoxError`]. Destructure the
+        /// variant with `DeadlineError::Terminal { error, .. }` to take it, e.g. to downcast it
+        /// back to the condition's error type.
+        error: BoxError,
+        /// The wait limit the condition was given, after scaling.
+        wait_limit: Duration,
+        /// How long the condition was waited on.
+        waited: Duration,
+        /// How many times the condition was evaluated, including the evaluation that failed.
+        polls: u64,
+        /// The message set on the builder, if any.
+        message: Option<Box<str>>,
+        /// The condition waited on.
+        condition: Option<Box<str>>,
+    },
+}
+
+impl DeadlineError {
+    pub(crate) fn timed_out(wait_limit: Duration, waited: Duration, polls: u64) -> Self {
+        Self::TimedOut {
+            wait_limit,
+            waited,
+            polls,
+            min_polls: 0,
+            message: None,
+            condition: None,
+            observed: Box::default(),
+            evaluating: None,
+            diagnostics: None,
+        }
+    }
+
+    pub(crate) fn polls_exhausted(wait_limit: Duration, waited: Duration, polls: u64) -> Self {
+        Self::PollsExhausted {
+            wait_limit,
+            waited,
+            polls,
+            message: None,
+            condition: None,
+            observed: Box::default(),
+            evaluating: None,
+            diagnostics: None,
+        }
+    }
+
+    #[cfg(feature = "tokio-util")]
+    pub(crate) fn cancelled(wait_limit: Duration, waited: Duration, polls: u64) -> Self {
+        Self::Cancelled {
+            wait_limit,
+            waited,
+            polls,
+            message: None,
+            condition: None,
+        }
+    }
+
+    pub(crate) fn terminal(
+        error: BoxError,
+        wait_limit: Duration,
+        waited: Duration,
+        polls: u64,
+    ) -> Self {
+        Self::Terminal {
+            error,
+            wait_limit,
+            waited,
+            polls,
+            message: None,
+            condition: None,
+        }
+    }
+
+    /// Adds `by` to the time waited, for time spent before polling started.
+    pub(crate) fn delayed(mut self, by: Duration) -> Self {
+        match &mut self {
+            Self::TimedOut { waited, .. }
+            | Self::PollsExhausted { waited, .. }
+            | Self::Cancelled { waited, .. }
+            | Self::Terminal { waited, .. } => *waited += by,
+        }
+        self
+    }
+
+    pub(crate) fn with_message(mut self, with: Option<String>) -> Self {
+        match &mut self {
+            Self::TimedOut { message, .. }
+            | Self::PollsExhausted { message, .. }
+            | Self::Cancelled { message, .. }
+            | Self::Terminal { message, .. } => *message = with.map(String::into_boxed_str),
+        }
+        self
+    }
+
+    pub(crate) fn with_wait_limit(mut self, with: Duration) -> Self {
+        match &mut self {
+            Self::TimedOut { wait_limit, .. }
+            | Self::PollsExhausted { wait_limit, .. }
+            | Self::Cancelled { wait_limit, .. }
+            | Self::Terminal { wait_limit, .. } => *wait_limit = with,
+        }
+        self
+    }
+
+    pub(crate) fn with_min_polls(mut self, with: u64) -> Self {
+        if let Self::TimedOut { min_polls, .. } = &mut self {
+            *min_polls = with;
+        }
+        self
+    }
+
+    pub(crate) fn with_observed(mut self, with: Vec<String>) -> Self {
+        if let Self::TimedOut { observed, .. } | Self::PollsExhausted { observed, .. } = &mut self {
+            *observed = with.into_boxed_slice();
+        }
+        self
+    }
+
+    pub(crate) fn with_evaluating(mut self, with: Option<Duration>) -> Self {
+        if let Self::TimedOut { evaluating, .. } | Self::PollsExhausted { evaluating, .. } =
+            &mut self
+        {
+            *evaluating = with;
+        }
+        self
+    }
+
+    pub(crate) fn with_diagnostics(mut self, with: Option<String>) -> Self {
+        if let Self::TimedOut { diagnostics, .. } | Self::PollsExhausted { diagnostics, .. } =
+            &mut self
+        {
+            *diagnostics = with.map(String::into_boxed_str);
+        }
+        self
+    }
+
+    /// Sets the condition the error describes.
+    #[doc(hidden)]
+    pub fn with_condition(mut self, with: String) -> Self {
+        match &mut self {
+            Self::TimedOut { condition, .. }
+            | Self::PollsExhausted { condition, .. }
+            | Self::Cancelled { condition, .. }
+            | Self::Terminal { condition, .. } => *condition = Some(with.into_boxed_str()),
+        }
+        self
+    }
+
+    /// Returns whether the condition wasn't met in time: the wait limit elapsed, or the polls
+    /// ran out.
+    pub fn is_timed_out(&self) -> bool {
+        matches!(self, Self::TimedOut { .. } | Self::PollsExhausted { .. })
+    }
+
+    /// Returns whether the wait was cancelled.
+    pub fn is_cancelled(&self) -> bool {
+        matches!(self, Self::Cancelled { .. })
+    }
+
+    /// Returns whether the condition failed with an error.
+    pub fn is_terminal(&self) -> bool {
+        matches!(self, Self::Terminal { .. })
+    }
+
+    /// Returns the wait limit the condition was given, after scaling.
+    pub fn wait_limit(&self) -> Duration {
+        match self {
+            Self::TimedOut { wait_limit, .. }
+            | Self::PollsExhausted { wait_limit, .. }
+            | Self::Cancelled { wait_limit, .. }
+            | Self::Terminal { wait_limit, .. } => *wait_limit,
+        }
+    }
+
+    /// Returns the condition waited on, if known.
+    pub fn condition(&self) -> Option<&str> {
+        match self {
+            Self::TimedOut { condition, .. }
+            | Self::PollsExhausted { condition, .. }
+            | Self::Cancelled { condition, .. }
+            | Self::Terminal { condition, .. } => condition.as_deref(),
+        }
+    }
+
+    /// Returns the last snapshots recorded through
+    /// [`Deadline::record_history`](crate::Deadline::record_history), oldest first.
+    pub fn observed(&self) -> &[String] {
+        match self {
+            Self::TimedOut { observed, .. } | Self::PollsExhausted { observed, .. } => observed,
+            Self::Cancelled { .. } | Self::Terminal { .. } => &[],
+        }
+    }
+
+    /// Returns the diagnostics returned by the
+    /// [`Deadline::on_timeout`](crate::Deadline::on_timeout) closure, if any.
+    pub fn diagnostics(&self) -> Option<&str> {
+        match self {
+            Self::TimedOut { diagnostics, .. } | Self::PollsExhausted { diagnostics, .. } => {
+                diagnostics.as_deref()
+            }
+            Self::Cancelled { .. } | Self::Terminal { .. } => None,
+        }
+    }
+
+    /// Returns how long the condition was waited on.
+    pub fn waited(&self) -> Duration {
+        match self {
+            Self::TimedOut { waited, .. }
+            | Self::PollsExhausted { waited, .. }
+            | Self::Cancelled { waited, .. }
+            | Self::Terminal { waited, .. } => *waited,
+        }
+    }
+
+    /// Returns how long was spent evaluating the condition, if measured through
+    /// [`Deadline::back_pressure`](crate::Deadline::back_pressure). The rest of the time waited
+    /// was spent sleeping.
+    pub fn evaluating(&self) -> Option<Duration> {
+        match self {
+            Self::TimedOut { evaluating, .. } | Self::PollsExhausted { evaluating, .. } => {
+                *evaluating
+            }
+            Self::Cancelled { .. } | Self::Terminal { .. } => None,
+        }
+    }
+
+    /// Returns how many times the condition was evaluated.
+    pub fn polls(&self) -> u64 {
+        match self {
+            Self::TimedOut { polls, .. }
+            | Self::PollsExhausted { polls, .. }
+            | Self::Cancelled { polls, .. }
+            | Self::Terminal { polls, .. } => *polls,
+        }
+    }
+}
+
+impl fmt::Display for DeadlineError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (Self::TimedOut {
+            waited,
+            polls,
+            message,
+            condition,
+            ..
+        }
+        | Self::PollsExhausted {
+            waited,
+            polls,
+            message,
+            condition,
+            ..
+        }
+        | Self::Cancelled {
+            waited,
+            polls,
+            message,
+            condition,
+            ..
+        }
+        | Self::Terminal {
+            waited,
+            polls,
+            message,
+            condition,
+            ..
+        }) = self;
+        if let Some(message) = message {
+            write!(f, "{message}: ")?;
+        }
+
+        match self {
+            Self::TimedOut {
+                wait_limit,
+                min_polls,
+                ..
+            } => {
+                write!(f, "deadline of {wait_limit:?} has elapsed")?;
+                if let Some(condition) = condition {
+                    write!(f, " for condition: {condition}")?;
+                }
+                write!(f, " after {waited:?} and {polls} polls")?;
+                if *min_polls > 0 {
+                    write!(f, " (at least {min_polls} requested)")?;
+                }
+            }
+            Self::PollsExhausted { .. } => {
+                write!(f, "condition ")?;
+                if let Some(condition) = condition {
+                    write!(f, "{condition} ")?;
+                }
+                write!(f, "still false after {polls} polls and {waited:?}")?;
+            }
+            Self::Cancelled { wait_limit, .. } => {
+                write!(f, "deadline of {wait_limit:?} was cancelled")?;
+                if let Some(condition) = condition {
+                    write!(f, " for condition: {condition}")?;
+                }
+                return write!(f, " after {waited:?} and {polls} polls");
+            }
+            Self::Terminal { error, .. } => {
+                write!(f, "condition ")?;
+                if let Some(condition) = condition {
+                    write!(f, "{condition} ")?;
+                }
+                return write!(f, "failed after {polls} polls and {waited:?}: {error}");
+            }
+        }
+
+        let (Self::TimedOut {
+            observed,
+            evaluating,
+            diagnostics,
+            ..
+        }
+        | Self::PollsExhausted {
+            observed,
+            evaluating,
+            diagnostics,
+            ..
+        }) = self
+        else {
+            return Ok(());
+        };
+
+        if let Some(evaluating) = evaluating {
+            write!(
+                f,
+                ", spending {evaluating:?} evaluating the condition and {:?} sleeping",
+                waited.saturating_sub(*evaluating)
+            )?;
+        }
+
+        if !observed.is_empty() {
+            // Every evaluation was false, so any missing from the history were dropped from its
+            // front.
+            write!(f, ", observed: [")?;
+            if *polls > observed.len() as u64 {
+                write!(f, "..., ")?;
+            }
+            write!(f, "{}]", observed.join(", "))?;
+        }
+
+        if let Some(diagnostics) = diagnostics {
+            write!(f, "\n\ndiagnostics:\n{diagnostics}")?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for DeadlineError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Terminal { error, .. } => Some(&**error),
+            _ => None,
+        }
+    }
+}
+
+/// A panic caught while evaluating a condition.
+#[doc(hidden)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConditionPanic {
+    /// How long after the start of the wait the condition panicked.
+    pub after: Duration,
+    /// The panic's message, if its payload was a string.
+    pub message: String,
+}
+
+impl ConditionPanic {
+    pub(crate) fn new(after: Duration, payload: &(dyn Any + Send)) -> Self {
+        let message = if let Some(message) = payload.downcast_ref::<&str>() {
+            (*message).to_owned()
+        } else if let Some(message) = payload.downcast_ref::<String>() {
+            message.clone()
+        } else {
+            "Box<dyn Any>".to_owned()
+        };
+
+        Self { after, message }
+    }
+}
@@ -0,0 +1,229 @@
+//! How the timeout panics of the macros are worded, replaceable for a whole test suite.
+
+use core::{
+    fmt,
+    sync::atomic::{AtomicBool, Ordering},
+    time::Duration,
+};
+use std::{
+    borrow::Cow,
+    panic,
+    sync::{Once, PoisonError, RwLock},
+    thread,
+};
+
+/// Builds a panic message from a [`DeadlineFailure`].
+type Formatter = fn(&DeadlineFailure<'_>) -> String;
+
+/// The formatter set through [`set_failure_formatter`], if any.
+static FORMATTER: RwLock<Option<Formatter>> = RwLock::new(None);
+
+/// Whether [`panic_with_failures`] was called.
+static STRUCTURED: AtomicBool = AtomicBool::new(false);
+
+/// What the macros know about a condition that wasn't met in time, for a formatter registered
+/// with [`set_failure_formatter`] to build the panic message from.
+///
+/// Its `Display` implementation is the message the macros panic with by default. After
+/// [`panic_with_failures`], they panic with a `DeadlineFailure<'static>` as the payload instead.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct DeadlineFailure<'a> {
+    /// The condition waited on, as written in the macro.
+    pub condition: Cow<'a, str>,
+    /// The message passed to the macro, if any.
+    pub message: Option<Cow<'a, str>>,
+    /// The wait limit passed to the macro, before scaling.
+    pub wait_limit: Duration,
+    /// How long the condition was waited on.
+    pub elapsed: Duration,
+    /// How many times the condition was evaluated.
+    pub polls: u64,
+    /// The default message of the macros wording it differently from `deadline!`, without the
+    /// message passed to the macro.
+    wording: Option<Cow<'a, str>>,
+}
+
+impl<'a> DeadlineFailure<'a> {
+    #[doc(hidden)]
+    pub fn new(
+        condition: &'a str,
+        message: Option<&'a str>,
+        wait_limit: Duration,
+        elapsed: Duration,
+        polls: u64,
+    ) -> Self {
+        Self {
+            condition: Cow::Borrowed(condition),
+            message: message.map(Cow::Borrowed),
+            wait_limit,
+            elapsed,
+            polls,
+            wording: None,
+        }
+    }
+
+    /// Replaces the default message, for the macros reporting more than `deadline!` does.
+    #[doc(hidden)]
+    pub fn worded(mut self, wording: String) -> Self {
+        self.wording = Some(Cow::Owned(wording));
+        self
+    }
+
+    /// Returns a copy owning its strings, e.g. to keep it past the macro's scope.
+    pub fn into_owned(self) -> DeadlineFailure<'static> {
+        DeadlineFailure {
+            condition: Cow::Owned(self.condition.into_owned()),
+            message: self.message.map(|message| Cow::Owned(message.into_owned())),
+            wait_limit: self.wait_limit,
+            elapsed: self.elapsed,
+            polls: self.polls,
+            wording: self.wording.map(|wording| Cow::Owned(wording.into_owned())),
+        }
+    }
+
+    /// Returns the panic message: the registered formatter's, or else the default one.
+    #[doc(hidden)]
+    pub fn panic_message(&self) -> String {
+        match *FORMATTER.read().unwrap_or_else(PoisonError::into_inner) {
+            Some(formatter) => formatter(self),
+            None => self.to_string(),
+        }
+    }
+
+    /// Panics with the panic message, or with the failure itself after [`panic_with_failures`].
+    #[doc(hidden)]
+    #[track_caller]
+    pub fn panic(self) -> ! {
+        if STRUCTURED.load(Ordering::Relaxed) {
+            self.panic_with_payload()
+        }
+        panic!("{}", self.panic_message())
+    }
+
+    /// Warns about the failure instead of panicking, for [`deadline_warn!`](crate::deadline_warn).
+    #[doc(hidden)]
+    pub fn warn(&self) {
+        warn_only_timeout(&self.panic_message());
+    }
+
+    /// Panics with the failure itself as the payload.
+    #[track_caller]
+    pub(crate) fn panic_with_payload(self) -> ! {
+        panic::panic_any(self.into_owned())
+    }
+}
+
+impl fmt::Display for DeadlineFailure<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(message) = &self.message {
+            write!(f, "{message}: ")?;
+        }
+        if let Some(wording) = &self.wording {
+            return f.write_str(wording);
+        }
+        write!(
+            f,
+            "the deadline has elapsed for condition: {} after {:?} (waited {:?})",
+            self.condition, self.wait_limit, self.elapsed
+        )
+    }
+}
+
+/// Reports the timeout of a warn-only deadline on stderr, and through `log` or `tracing` when
+/// enabled, prefixed for these to be told apart from the panics of the other deadlines.
+pub(crate) fn warn_only_timeout(timeout: &dyn fmt::Display) {
+    eprintln!("deadline [warn-only]: {timeout}");
+    #[cfg(feature = "log")]
+    log::warn!("deadline [warn-only]: {timeout}");
+    #[cfg(feature = "tracing")]
+    tracing::warn!("deadline [warn-only]: {timeout}");
+}
+
+/// Registers the formatter building the panic message of the macros when their condition isn't
+/// met in time, e.g. to emit JSON for tooling parsing the test output.
+///
+/// This applies to the timeout panics of every macro, while panics raised by the condition and
+/// failures other than a timeout, such as a closed channel, are left as they are. The formatter is
+/// global to the process and replaces any registered before, so it's best registered once, before
+/// the tests run. Without one, the message is the [`DeadlineFailure`]'s `Display` output.
+///
+/// # Examples
+///
+/// ```rust,should_panic
+/// # #[tokio::main]
+/// # async fn main() {
+/// #     use std::time::Duration;
+/// #
+/// #     use deadline::{deadline, DeadlineFailure};
+/// deadline::set_failure_formatter(|failure: &DeadlineFailure<'_>| {
+///     format!(
+///         r#"{{"condition": {:?}, "polls": {}}}"#,
+///         failure.condition, failure.polls
+///     )
+/// });
+///
+/// let x = 1;
+/// deadline!(Duration::from_millis(1), move || x == 2);
+/// # }
+/// ```
+pub fn set_failure_formatter(formatter: Formatter) {
+    *FORMATTER.write().unwrap_or_else(PoisonError::into_inner) = Some(formatter);
+}
+
+/// Makes the macros panic with their [`DeadlineFailure`] as the payload rather than a `String`,
+/// for custom test harnesses to downcast and inspect.
+///
+/// This applies to the same panics as [`set_failure_formatter`], the timeout panics of every
+/// macro. It also installs a panic hook
+/// printing these payloads as the default hook would print the message, and leaving other panics
+/// to the hook installed before. As the standard test harness only matches the `expected` message
+/// of `#[should_panic]` against `String` and `&str` payloads, such tests fail once it's called.
+/// Like the formatter, this is global to the process and best called once, before the tests run.
+///
+/// # Examples
+///
+/// ```rust
+/// # #[tokio::main]
+/// # async fn main() {
+/// #     use std::time::Duration;
+/// #
+/// #     use deadline::{deadline, DeadlineFailure};
+/// deadline::panic_with_failures();
+///
+/// let x = 1;
+/// let panic = tokio::spawn(async move {
+///     deadline!(Duration::from_millis(1), move || x == 2);
+/// })
+/// .await
+/// .unwrap_err()
+/// .into_panic();
+///
+/// let failure = panic.downcast_ref::<DeadlineFailure<'static>>().unwrap();
+/// assert_eq!(failure.condition, "x == 2");
+/// assert!(failure.polls > 0);
+/// # }
+/// ```
+pub fn panic_with_failures() {
+    static HOOK: Once = Once::new();
+
+    HOOK.call_once(|| {
+        let previous = panic::take_hook();
+        panic::set_hook(Box::new(move |info| {
+            match info.payload().downcast_ref::<DeadlineFailure<'static>>() {
+                Some(failure) => {
+                    let location = info
+                        .location()
+                        .map_or_else(String::new, |location| format!(" at {location}"));
+                    eprintln!(
+                        "\nthread '{}' panicked{location}:\n{}",
+                        thread::current().name().unwrap_or("<unnamed>"),
+                        failure.panic_message()
+                    );
+                }
+                None => previous(info),
+            }
+        }));
+    });
+    STRUCTURED.store(true, Ordering::Relaxed);
+}
@@ -1,146 +1,7488 @@
 //! A one-macro crate to ensure assertions meet their deadlines.
+//!
+//! # Runtimes
+//!
+//! The timers are provided by [`tokio`](https://docs.rs/tokio) by default. To use the crate with
+//! another runtime, disable the default features and enable exactly one of:
+//!
+//! - `async-std`: timers from [`async-std`](https://docs.rs/async-std).
+//! - `smol`: timers from [`smol`](https://docs.rs/smol).
+//! - `wasm`: `setTimeout` timers from [`gloo-timers`](https://docs.rs/gloo-timers), for tests
+//!   running in a browser, e.g. under `wasm-bindgen-test`.
+//! - `glommio`: timers from [`glommio`](https://docs.rs/glommio), for thread-per-core executors.
+//! - `minimal`: no timers and no dependencies, for any executor. A sleep yields to the executor
+//!   until it's over, waking the task straight away each time, so the thread is never idle while
+//!   waiting, and futures waited on, as by [`deadline_poll_fn!`], are polled each time too. This
+//!   suits libraries only pulling in an executor for their tests, not long waits.
+//!
+//! The macros behave identically regardless of the selected backend.
+//!
+//! Runtimes built on tokio's, like [`actix-rt`](https://docs.rs/actix-rt) behind
+//! `#[actix_web::test]` and `#[actix_rt::test]`, work with the default `tokio` backend: actix runs
+//! its tasks on a `LocalSet` within a current-thread tokio runtime with the timer enabled, so
+//! there's no separate backend for it.
+//!
+//! The browser's event loop is single-threaded, so with the `wasm` backend the task always waits
+//! for a timer between evaluations, even for a zero poll interval, rather than waking itself
+//! straight away. [`deadline_until!`], [`deadline_blocking!`] and [`deadline_blocking_bool`] aren't
+//! supported, as they rely on `std::time::Instant` and thread sleeps, which browsers don't
+//! provide. Panics in the condition can't be caught when panicking aborts, as is the default on
+//! `wasm32`.
+//!
+//! # Scaling
+//!
+//! Setting the `DEADLINE_SCALE` environment variable to a positive number multiplies the wait
+//! limit of every macro waiting for something to happen, which helps on CI machines slower than the
+//! ones the limits were tuned on. It's read once, on first use, and invalid values are ignored
+//! with a warning. Only the wait limit is scaled: poll intervals and the durations passed to
+//! [`deadline_sustained!`] and [`deadline_never!`], and the settle period of [`deadline_settle!`],
+//! are left as they are.
+//!
+//! Debug builds can also be given more time than release builds with [`set_debug_scale`], picked
+//! automatically from whether `debug_assertions` are enabled. Its factor multiplies
+//! `DEADLINE_SCALE`'s.
+//!
+//! # Paused time
+//!
+//! With the `tokio` backend, all timings go through the runtime's clock, so the macros work with
+//! [paused time](https://docs.rs/tokio/latest/tokio/time/fn.pause.html): the task sleeps between
+//! evaluations, letting the runtime auto-advance the clock to whichever timer is due next. The
+//! exception is a zero poll interval, which keeps the task runnable at all times and therefore
+//! never lets paused time advance.
+//!
+//! # Multi-threaded runtimes
+//!
+//! The futures the macros await are [`Send`] as long as the condition closure is [`Send`] and
+//! [`Sync`], so the macros can be used inside tasks spawned on a multi-threaded runtime. The
+//! exceptions are the `wasm` and `glommio` backends, whose timers are tied to the thread they're
+//! created on: their futures aren't [`Send`], which suits the executors running them locally,
+//! like `wasm-bindgen-futures` or glommio's.
+//!
+//! # Cancellation
+//!
+//! The futures the macros await, and those returned by [`deadline_future`] and [`Deadline::wait`],
+//! are cancellation safe: they can be dropped at any point, e.g. when another branch of a
+//! `select!` completes first, without leaving anything behind. The condition is evaluated
+//! synchronously within a poll, so dropping the future never interrupts an evaluation halfway,
+//! and the only other state they hold, like the recorded history or a tokio `Notify` waiter, is
+//! released on drop. Waiting on the same state again afterwards starts a fresh deadline.
+//!
+//! The exception is [`deadline_spawn_blocking!`], whose evaluations run on the blocking thread
+//! pool: one in progress when the future is dropped runs to completion in the background, and its
+//! result is discarded.
+//!
+//! To stop waiting without dropping the future, e.g. from another task, pass a tokio-util
+//! `CancellationToken` to [`Deadline::cancel_on`]: cancelling it returns a
+//! [`DeadlineError::Cancelled`], which tells it apart from a timeout.
+//!
+//! # Runtime flavors
+//!
+//! Between evaluations, the macros either sleep on a timer, freeing the thread for other tasks, or
+//! with a zero poll interval yield to the runtime, which still lets its other tasks and its timer
+//! and IO drivers run before the condition is evaluated again. Either way, on a single-threaded
+//! runtime like tokio's current-thread flavor, the default for `#[tokio::test]`, a task spawned to
+//! satisfy the condition gets to run. [`SpinSleeper`] wakes the task straight away instead, so it
+//! falls back to yielding on tokio's current-thread flavor, and only spins on multi-threaded
+//! runtimes. The condition itself still runs on the test's thread: one that blocks, e.g. on a lock
+//! held by a spawned task, blocks the whole current-thread runtime, as
+//! [`deadline_spawn_blocking!`] avoids.
+//!
+//! # Tracing and logging
+//!
+//! Enabling the `tracing` feature instruments the polling macros with
+//! [`tracing`](https://docs.rs/tracing): each call opens a `deadline` span with the condition and
+//! the wait limit as fields, every evaluation of the condition emits a trace-level event, and the
+//! outcome is reported in a debug-level event along with the number of evaluations. Nothing is
+//! emitted, nor computed, with the feature disabled.
+//!
+//! For projects using [`log`](https://docs.rs/log) instead, the `log` feature logs the condition
+//! and the wait limit at the debug level when a polling macro starts waiting, and the time waited
+//! and the number of evaluations at the warn level when the deadline elapses. The two features are
+//! independent and can be enabled together.
+//!
+//! # `no_std`
+//!
+//! The `std` feature, implied by each of the backends, is required for the macros and everything
+//! built on the runtime's timers. Without it the crate is `no_std` and provides the polling core
+//! on its own, as [`with_timeout`], which races the condition against any timeout future, e.g. a
+//! timer from an embedded executor.
+//!
+//! # Testing error handling
+//!
+//! With the `test-util` feature, [`test_util::force_timeout`] makes every deadline on the current
+//! thread time out immediately, to test the code handling [`DeadlineError`]s or timeout panics
+//! without waiting. The feature is meant for `[dev-dependencies]`, and fails to compile without
+//! `debug_assertions` so that it can't reach a release build, which rules out testing with it under
+//! `--release` unless the profile sets `debug-assertions = true`.
+//!
+//! # Panics
+//!
+//! The panicking macros raise their panics from the expanded macro body rather than from the
+//! crate's internals, so the reported location is always the macro's call site.
 
-use core::{
-    future::Future,
-    pin::Pin,
-    task::{Context, Poll},
-    time::Duration,
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
+
+#[cfg(feature = "std")]
+mod budget;
+#[cfg(feature = "std")]
+mod builder;
+mod conditions;
+#[cfg(feature = "std")]
+mod default;
+#[cfg(feature = "std")]
+mod error;
+#[cfg(feature = "std")]
+mod failure;
+#[cfg(feature = "tokio")]
+mod lock;
+#[cfg(feature = "std")]
+mod mismatch;
+#[cfg(feature = "std")]
+pub mod prelude;
+mod ready;
+#[cfg(feature = "std")]
+mod rt;
+#[cfg(feature = "std")]
+mod scale;
+#[cfg(feature = "std")]
+mod sleeper;
+#[cfg(feature = "std")]
+mod strategy;
+#[cfg(feature = "test-util")]
+pub mod test_util;
+mod wait_limit;
+mod with_timeout;
+
+use core::time::Duration;
+#[cfg(feature = "std")]
+use core::{cell::Cell, future::Future, iter, task::Poll};
+#[cfg(feature = "std")]
+use std::panic::AssertUnwindSafe;
+
+#[cfg(feature = "std")]
+pub use budget::Budget;
+#[cfg(feature = "std")]
+pub use builder::{Deadline, DeadlineOutcome};
+#[cfg(feature = "std")]
+#[doc(hidden)]
+pub use builder::{Diagnostics, OnTimeout, Snapshot};
+#[doc(hidden)]
+pub use conditions::Conditions;
+#[cfg(feature = "std")]
+pub use default::{
+    default_poll_strategy, default_wait_limit, set_default_wait_limit, set_defaults, Defaults,
+    DEFAULT_WAIT_LIMIT,
 };
+#[cfg(feature = "std")]
+#[doc(hidden)]
+pub use error::ConditionPanic;
+#[cfg(feature = "std")]
+pub use error::{BoxError, DeadlineError};
+#[cfg(feature = "std")]
+pub use failure::{panic_with_failures, set_failure_formatter, DeadlineFailure};
+#[cfg(feature = "tokio")]
+#[doc(hidden)]
+pub use lock::AsyncLock;
+#[cfg(feature = "std")]
+#[doc(hidden)]
+pub use mismatch::{mismatch, Mismatch};
+pub use ready::IsReady;
+#[cfg(feature = "std")]
+#[doc(hidden)]
+pub use rt::Instant;
+#[cfg(feature = "std")]
+use scale::scale;
+#[cfg(feature = "std")]
+pub use scale::set_debug_scale;
+#[cfg(feature = "std")]
+pub use sleeper::{RuntimeSleeper, Sleeper, SpinSleeper};
+#[cfg(feature = "std")]
+pub use strategy::PollStrategy;
+#[doc(hidden)]
+pub use wait_limit::{IntoWaitLimit, RefIntoWaitLimit};
+pub use with_timeout::{with_timeout, TimedOut};
 
-use tokio::time::{error::Elapsed, timeout};
+/// The interval [`deadline!`] waits between evaluations of its condition.
+pub const DEFAULT_POLL_INTERVAL: Duration = Duration::from_millis(1);
 
-struct Fut<'a>(&'a dyn Fn() -> bool);
+/// The interval [`deadline_backoff!`] waits before the second evaluation of its condition.
+pub const DEFAULT_BACKOFF_START: Duration = Duration::from_millis(1);
 
-impl<'a> Future for Fut<'a> {
-    type Output = ();
+/// The longest interval [`deadline_backoff!`] waits between evaluations of its condition.
+pub const DEFAULT_BACKOFF_CAP: Duration = Duration::from_millis(100);
 
-    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
-        if self.0() {
-            Poll::Ready(())
-        } else {
-            cx.waker().wake_by_ref();
-            Poll::Pending
-        }
+/// Renders a stringified condition the way the macros' panic messages do.
+///
+/// Runs of whitespace, including line breaks, are collapsed into single spaces, and the `move ||`
+/// or `||` of a closure without arguments is stripped, so that `stringify!(move || x == y)` renders
+/// as `x == y`. This lets assertion macros built on top of the crate describe their conditions
+/// consistently with it.
+///
+/// # Examples
+///
+/// ```rust
+/// assert_eq!(
+///     deadline::normalize_condition(stringify!(move || {
+///         x == y
+///     })),
+///     "{ x == y }"
+/// );
+/// ```
+#[cfg(feature = "std")]
+pub fn normalize_condition(condition: &str) -> String {
+    let normalized = condition.split_whitespace().collect::<Vec<_>>().join(" ");
+    let closure_body = normalized
+        .strip_prefix("move || ")
+        .or_else(|| normalized.strip_prefix("|| "));
+    match closure_body {
+        Some(body) => body.to_owned(),
+        None => normalized,
     }
 }
 
+#[cfg(feature = "std")]
+/// Wraps the future in a `tracing` span describing the deadline, when the `tracing` feature is
+/// enabled, and logs the start of the wait when the `log` feature is.
 #[doc(hidden)]
-pub async fn deadline_inner<F: Fn() -> bool + 'static>(
+pub fn instrument<F: Future>(
+    future: F,
+    condition: &'static str,
     wait_limit: Duration,
-    condition: F,
-) -> Result<(), Elapsed> {
-    timeout(wait_limit, Fut(&condition)).await
+) -> impl Future<Output = F::Output> {
+    #[cfg(feature = "log")]
+    let future = async move {
+        log::debug!(
+            "waiting up to {wait_limit:?} for condition: {}",
+            normalize_condition(condition)
+        );
+        future.await
+    };
+
+    #[cfg(feature = "tracing")]
+    {
+        tracing::Instrument::instrument(
+            future,
+            tracing::debug_span!(
+                "deadline",
+                condition = %normalize_condition(condition),
+                wait_limit = ?wait_limit,
+            ),
+        )
+    }
+
+    #[cfg(not(feature = "tracing"))]
+    {
+        let _ = (condition, wait_limit);
+        future
+    }
 }
 
-/// Requires a condition closure to return `true` before the specified duration has elapsed.
+#[cfg(feature = "std")]
+/// The intervals of a backoff doubling from `start` up to `cap`.
+fn backoff(start: Duration, cap: Duration) -> impl Iterator<Item = Duration> {
+    iter::successors(Some(start), move |delay| {
+        Some(delay.saturating_mul(2).min(cap))
+    })
+}
+
+#[cfg(feature = "std")]
+/// Evaluates the condition until it returns `Some` or `wait_limit` has elapsed, sleeping for each
+/// of the `intervals` in turn between evaluations. If `max_polls` is set, gives up after that many
+/// evaluations, the condition always being evaluated at least once.
 ///
-/// This will panic if the provided closure doesn't evaluate to `true` before the provided duration
-/// expires. Internally, it creates a [`Future`] from the closure that is polled until it returns
-/// `true` or times out. This ensures the call is non-blocking to the async runtime.
+/// This is the one polling loop the macros share: those returning `()`, a value or a
+/// [`DeadlineOutcome`] differ only in the `T` their condition produces.
+async fn poll_until<T, S: Sleeper>(
+    sleeper: &S,
+    wait_limit: Duration,
+    max_polls: Option<u64>,
+    intervals: impl Iterator<Item = Duration>,
+    condition: impl FnMut() -> Option<T>,
+) -> Result<T, DeadlineError> {
+    poll_at_least(sleeper, wait_limit, 0, max_polls, intervals, condition).await
+}
+
+#[cfg(feature = "std")]
+/// Like [`poll_until`], but evaluates the condition at least `min_polls` times before timing out,
+/// shortening the intervals to fit them within `wait_limit`. Evaluations still missing once it has
+/// elapsed, e.g. after oversleeping on a coarse timer, are made back to back.
+async fn poll_at_least<T, S: Sleeper>(
+    sleeper: &S,
+    wait_limit: Duration,
+    min_polls: u64,
+    max_polls: Option<u64>,
+    mut intervals: impl Iterator<Item = Duration>,
+    mut condition: impl FnMut() -> Option<T>,
+) -> Result<T, DeadlineError> {
+    forced_timeout(wait_limit)?;
+    let started = sleeper.now();
+    let mut polls = 0u64;
+
+    loop {
+        let result = condition();
+        polls += 1;
+        #[cfg(feature = "tracing")]
+        tracing::trace!(polls, met = result.is_some(), "evaluated the condition");
+
+        if let Some(value) = result {
+            #[cfg(feature = "tracing")]
+            tracing::debug!(polls, "the condition was met");
+            return Ok(value);
+        }
+
+        let waited = sleeper.now().saturating_duration_since(started);
+        if max_polls.is_some_and(|max_polls| polls >= max_polls) {
+            #[cfg(feature = "tracing")]
+            tracing::debug!(polls, "the polls are exhausted");
+            #[cfg(feature = "log")]
+            log::warn!("the polls are exhausted after {waited:?} and {polls} polls");
+            return Err(DeadlineError::polls_exhausted(wait_limit, waited, polls));
+        }
+
+        let remaining = wait_limit.saturating_sub(waited);
+        let missing_polls = min_polls.saturating_sub(polls);
+        if remaining.is_zero() && missing_polls == 0 {
+            #[cfg(feature = "tracing")]
+            tracing::debug!(polls, "the deadline has elapsed");
+            #[cfg(feature = "log")]
+            log::warn!("the deadline has elapsed after {waited:?} and {polls} polls");
+            return Err(DeadlineError::timed_out(wait_limit, waited, polls));
+        }
+
+        // Clamp to the remaining time so the last evaluation lands on the deadline.
+        let mut interval = intervals.next().unwrap_or(remaining);
+        if missing_polls > 0 {
+            interval = interval.min(remaining / u32::try_from(missing_polls).unwrap_or(u32::MAX));
+        }
+        sleeper.sleep(interval.min(remaining)).await;
+    }
+}
+
+#[cfg(feature = "std")]
+/// Fails like a deadline that timed out straight away if [`test_util::force_timeout`] is in effect.
+pub(crate) fn forced_timeout(wait_limit: Duration) -> Result<(), DeadlineError> {
+    #[cfg(feature = "test-util")]
+    if test_util::forced() {
+        return Err(DeadlineError::timed_out(wait_limit, Duration::ZERO, 0));
+    }
+
+    let _ = wait_limit;
+    Ok(())
+}
+
+#[cfg(feature = "std")]
+/// Races the future against the wait limit on the runtime's timers.
+async fn timeout<F: Future>(wait_limit: Duration, future: F) -> Result<F::Output, DeadlineError> {
+    forced_timeout(wait_limit)?;
+    rt::timeout(wait_limit, future).await
+}
+
+/// Returns a future waiting for the condition to return `true`, evaluated every
+/// [`DEFAULT_POLL_INTERVAL`] for up to `wait_limit`.
 ///
-/// # Examples
+/// This is the building block the macros share, without their `.await` and assertion: the future
+/// can be held, combined with others in a `select!` or `join!`, and polled like any other. It
+/// resolves to the same [`DeadlineError`] as [`try_deadline!`], which is a thin wrapper around it.
+/// The wait limit is scaled by `DEADLINE_SCALE` like the macros'.
 ///
-/// Waiting for an `AtomicI32` to be incremented to `42`:
+/// # Examples
 ///
 /// ```rust
 /// # #[tokio::main]
 /// # async fn main() {
-/// #     use std::{
-/// #         sync::{
-/// #             atomic::{AtomicI32, Ordering},
-/// #             Arc,
-/// #         },
-/// #         time::Duration,
-/// #     };
+/// #     use std::time::Duration;
 /// #
-/// #     use deadline::deadline;
-/// let x = Arc::new(AtomicI32::new(41));
+/// let x = 42;
 /// let y = 42;
 ///
-/// let x_clone = x.clone();
-/// tokio::spawn(async move {
-///     tokio::time::sleep(Duration::from_millis(5)).await;
-///     x_clone.fetch_add(1, Ordering::SeqCst);
-/// });
-///
-/// deadline!(Duration::from_millis(10), move || {
-///     x.load(Ordering::Relaxed) == y
-/// });
+/// let (result, ()) = tokio::join!(
+///     deadline::deadline_future(Duration::from_millis(10), move || x == y),
+///     tokio::time::sleep(Duration::from_millis(1)),
+/// );
+/// result.unwrap();
 /// # }
 /// ```
-#[macro_export]
-macro_rules! deadline {
-    ($wait_limit: expr, $condition: expr) => {{
-        assert!(
-            $crate::deadline_inner($wait_limit, $condition)
-                .await
-                .is_ok(),
-            "the deadline has elapsed for condition: {}",
-            {
-                let normalized = stringify!($condition)
-                    .split_whitespace()
-                    .collect::<Vec<_>>()
-                    .join(" ");
-                if let Some(wo_prefix) = normalized.strip_prefix("move || ") {
-                    wo_prefix.to_owned()
+#[cfg(feature = "std")]
+pub fn deadline_future<R: IsReady, F: Fn() -> R>(
+    wait_limit: impl Into<Duration>,
+    condition: F,
+) -> impl Future<Output = Result<(), DeadlineError>> {
+    deadline_inner_with_interval(
+        &RuntimeSleeper,
+        wait_limit.into(),
+        DEFAULT_POLL_INTERVAL,
+        condition,
+    )
+}
+
+/// Blocks the current thread until the condition returns `true`, evaluated every
+/// [`DEFAULT_POLL_INTERVAL`] for up to `wait_limit`, and returns whether it did.
+///
+/// This is the non-panicking counterpart to [`deadline_blocking!`], for sync code checking the
+/// outcome itself, e.g. a property test's predicate. Like the macro, it doesn't need a runtime,
+/// and the wait limit is scaled by `DEADLINE_SCALE`. Panics raised by the closure aren't caught.
+///
+/// # Examples
+///
+/// ```rust
+/// # use std::time::Duration;
+/// #
+/// let x = 42;
+///
+/// assert!(deadline::deadline_blocking_bool(Duration::from_millis(10), move || x == 42));
+/// assert!(!deadline::deadline_blocking_bool(Duration::from_millis(1), move || x == 0));
+/// ```
+#[cfg(feature = "std")]
+pub fn deadline_blocking_bool<R: IsReady, F: Fn() -> R>(
+    wait_limit: impl Into<Duration>,
+    condition: F,
+) -> bool {
+    deadline_blocking_inner(wait_limit.into(), condition).is_ok()
+}
+
+#[cfg(feature = "std")]
+#[doc(hidden)]
+pub async fn deadline_inner_catch_unwind<S: Sleeper, R: IsReady, F: Fn() -> R>(
+    sleeper: &S,
+    wait_limit: Duration,
+    strategy: PollStrategy<'_>,
+    retry_panics: bool,
+    condition: F,
+) -> Result<(), (Option<ConditionPanic>, u64)> {
+    let started = sleeper.now();
+    let wait_limit = scale(wait_limit);
+    let mut last_panic = None;
+    let mut polls = 0;
+
+    let condition = || {
+        polls += 1;
+        match std::panic::catch_unwind(AssertUnwindSafe(|| condition().is_ready())) {
+            Ok(true) => Some(Ok(())),
+            Ok(false) => None,
+            Err(payload) => {
+                let after = sleeper.now().saturating_duration_since(started);
+                let panic = ConditionPanic::new(after, &*payload);
+                if retry_panics {
+                    last_panic = Some(panic);
+                    None
                 } else {
-                    normalized
+                    Some(Err(panic))
                 }
             }
+        }
+    };
+
+    let result = match strategy {
+        PollStrategy::Spin => {
+            poll_until(
+                sleeper,
+                wait_limit,
+                None,
+                iter::repeat(Duration::ZERO),
+                condition,
+            )
+            .await
+        }
+        PollStrategy::Interval(poll_interval) => {
+            poll_until(
+                sleeper,
+                wait_limit,
+                None,
+                iter::repeat(poll_interval),
+                condition,
+            )
+            .await
+        }
+        PollStrategy::Backoff { start, cap } => {
+            poll_until(sleeper, wait_limit, None, backoff(start, cap), condition).await
+        }
+        #[cfg(feature = "tokio")]
+        PollStrategy::Notify(notify) => {
+            poll_until(
+                &sleeper::NotifySleeper(notify),
+                wait_limit,
+                None,
+                iter::repeat(Duration::MAX),
+                condition,
+            )
+            .await
+        }
+        #[cfg(not(feature = "tokio"))]
+        PollStrategy::__Unconstructible(never, _) => match never {},
+    };
+
+    match result {
+        Ok(Ok(())) => Ok(()),
+        Ok(Err(panic)) => Err((Some(panic), polls)),
+        Err(_) => Err((last_panic, polls)),
+    }
+}
+
+#[cfg(feature = "std")]
+#[doc(hidden)]
+pub async fn deadline_inner_with_interval<S: Sleeper, R: IsReady, F: Fn() -> R>(
+    sleeper: &S,
+    wait_limit: Duration,
+    poll_interval: Duration,
+    condition: F,
+) -> Result<(), DeadlineError> {
+    Deadline::new(wait_limit)
+        .poll_interval(poll_interval)
+        .sleeper(sleeper)
+        .wait(condition)
+        .await
+        .map(drop)
+}
+
+#[cfg(feature = "std")]
+#[doc(hidden)]
+pub async fn deadline_ctx_inner<C: ?Sized, F: FnMut(&mut C) -> bool>(
+    wait_limit: Duration,
+    ctx: &mut C,
+    mut condition: F,
+) -> Result<(), DeadlineError> {
+    Deadline::new(wait_limit)
+        .wait(|| condition(ctx))
+        .await
+        .map(drop)
+}
+
+#[cfg(feature = "std")]
+#[doc(hidden)]
+pub async fn deadline_value_inner<T, F: Fn() -> Option<T>>(
+    wait_limit: Duration,
+    condition: F,
+) -> Result<T, DeadlineError> {
+    deadline_value_inner_with_interval(
+        &RuntimeSleeper,
+        wait_limit,
+        DEFAULT_POLL_INTERVAL,
+        condition,
+    )
+    .await
+}
+
+#[cfg(feature = "std")]
+#[doc(hidden)]
+pub async fn deadline_value_inner_with_interval<T, S: Sleeper, F: Fn() -> Option<T>>(
+    sleeper: &S,
+    wait_limit: Duration,
+    poll_interval: Duration,
+    condition: F,
+) -> Result<T, DeadlineError> {
+    poll_until(
+        sleeper,
+        scale(wait_limit),
+        None,
+        iter::repeat(poll_interval),
+        condition,
+    )
+    .await
+}
+
+#[cfg(feature = "std")]
+#[doc(hidden)]
+pub async fn deadline_try_inner<T, E, S: Sleeper, F: Fn() -> Poll<Result<T, E>>>(
+    sleeper: &S,
+    wait_limit: Duration,
+    condition: F,
+) -> Result<T, Result<E, DeadlineError>> {
+    let result = poll_until(
+        sleeper,
+        scale(wait_limit),
+        None,
+        iter::repeat(DEFAULT_POLL_INTERVAL),
+        || match condition() {
+            Poll::Ready(result) => Some(result),
+            Poll::Pending => None,
+        },
+    )
+    .await;
+
+    match result {
+        Ok(Ok(value)) => Ok(value),
+        Ok(Err(error)) => Err(Ok(error)),
+        Err(error) => Err(Err(error)),
+    }
+}
+
+#[cfg(feature = "std")]
+#[doc(hidden)]
+pub async fn deadline_then_inner<T, F: Fn() -> bool, G: FnOnce(()) -> T>(
+    wait_limit: Duration,
+    condition: F,
+    then: G,
+) -> Result<T, DeadlineError> {
+    let mut then = Some(then);
+
+    poll_until(
+        &RuntimeSleeper,
+        scale(wait_limit),
+        None,
+        iter::repeat(DEFAULT_POLL_INTERVAL),
+        || {
+            if condition() {
+                then.take().map(|then| then(()))
+            } else {
+                None
+            }
+        },
+    )
+    .await
+}
+
+#[cfg(feature = "std")]
+#[doc(hidden)]
+pub async fn deadline_ok_inner<T, E, S: Sleeper, F: Fn() -> Result<T, E>>(
+    sleeper: &S,
+    wait_limit: Duration,
+    condition: F,
+) -> Result<T, (Option<E>, DeadlineError)> {
+    let mut last = None;
+
+    let result = poll_until(
+        sleeper,
+        scale(wait_limit),
+        None,
+        iter::repeat(DEFAULT_POLL_INTERVAL),
+        || match condition() {
+            Ok(value) => Some(value),
+            Err(error) => {
+                last = Some(error);
+                None
+            }
+        },
+    )
+    .await;
+
+    // The last error is `None` if the deadline was forced to time out before any evaluation.
+    result.map_err(|error| (last, error))
+}
+
+#[cfg(feature = "std")]
+#[doc(hidden)]
+pub async fn deadline_eq_inner<T: PartialEq, S: Sleeper, F: Fn() -> T>(
+    sleeper: &S,
+    wait_limit: Duration,
+    actual: F,
+    expected: &T,
+) -> Result<(), (Option<T>, DeadlineError)> {
+    deadline_cmp_inner(sleeper, wait_limit, actual, |value| value == expected).await
+}
+
+#[cfg(feature = "std")]
+#[doc(hidden)]
+pub async fn deadline_cmp_inner<T, S: Sleeper, F: Fn() -> T, C: Fn(&T) -> bool>(
+    sleeper: &S,
+    wait_limit: Duration,
+    actual: F,
+    holds: C,
+) -> Result<(), (Option<T>, DeadlineError)> {
+    let mut last = None;
+
+    let result = poll_until(
+        sleeper,
+        scale(wait_limit),
+        None,
+        iter::repeat(DEFAULT_POLL_INTERVAL),
+        || {
+            let value = actual();
+            if holds(&value) {
+                Some(())
+            } else {
+                last = Some(value);
+                None
+            }
+        },
+    )
+    .await;
+
+    // The last value is `None` if the deadline was forced to time out before any evaluation.
+    result.map_err(|error| (last, error))
+}
+
+#[cfg(feature = "std")]
+#[doc(hidden)]
+pub async fn deadline_approx_inner<S: Sleeper, F: Fn() -> f64>(
+    sleeper: &S,
+    wait_limit: Duration,
+    actual: F,
+    target: f64,
+    epsilon: f64,
+) -> Result<(), (Option<f64>, DeadlineError)> {
+    let mut last = None;
+
+    let result = poll_until(
+        sleeper,
+        scale(wait_limit),
+        None,
+        iter::repeat(DEFAULT_POLL_INTERVAL),
+        || {
+            let value = actual();
+            last = Some(value);
+            // Any comparison involving NaN is false, so NaN never satisfies the condition.
+            ((value - target).abs() <= epsilon).then_some(())
+        },
+    )
+    .await;
+
+    result.map_err(|error| (last, error))
+}
+
+#[cfg(feature = "std")]
+#[doc(hidden)]
+pub async fn deadline_inner_with_backoff<S: Sleeper, R: IsReady, F: Fn() -> R>(
+    sleeper: &S,
+    wait_limit: Duration,
+    start: Duration,
+    cap: Duration,
+    condition: F,
+) -> Result<(), DeadlineError> {
+    Deadline::new(wait_limit)
+        .backoff(start, cap)
+        .sleeper(sleeper)
+        .wait(condition)
+        .await
+        .map(drop)
+}
+
+#[cfg(feature = "std")]
+#[doc(hidden)]
+pub async fn deadline_strategy_inner<R: IsReady, F: Fn() -> R>(
+    wait_limit: Duration,
+    strategy: PollStrategy<'_>,
+    condition: F,
+) -> Result<(), DeadlineError> {
+    let sleeper = &RuntimeSleeper;
+    match strategy {
+        PollStrategy::Spin => {
+            deadline_inner_with_interval(sleeper, wait_limit, Duration::ZERO, condition).await
+        }
+        PollStrategy::Interval(poll_interval) => {
+            deadline_inner_with_interval(sleeper, wait_limit, poll_interval, condition).await
+        }
+        PollStrategy::Backoff { start, cap } => {
+            deadline_inner_with_backoff(sleeper, wait_limit, start, cap, condition).await
+        }
+        #[cfg(feature = "tokio")]
+        PollStrategy::Notify(notify) => deadline_notify_inner(wait_limit, notify, condition).await,
+        #[cfg(not(feature = "tokio"))]
+        PollStrategy::__Unconstructible(never, _) => match never {},
+    }
+}
+
+#[cfg(feature = "std")]
+#[doc(hidden)]
+pub async fn deadline_progress_inner<S: Sleeper, F: Fn() -> u64, P: Fn(u64) -> bool>(
+    sleeper: &S,
+    wait_limit: Duration,
+    value: F,
+    done: P,
+) -> Result<u64, (Option<u64>, DeadlineError)> {
+    let last = Cell::new(None);
+    let moving = Cell::new(false);
+
+    // Halve the interval while the value is changing, double it while it's stalled. This is
+    // computed lazily as each interval is needed, to account for the latest evaluation.
+    let mut interval: Option<Duration> = None;
+    let intervals = iter::from_fn(|| {
+        let next = match interval {
+            None => DEFAULT_BACKOFF_START,
+            Some(interval) if moving.get() => (interval / 2).max(DEFAULT_BACKOFF_START),
+            Some(interval) => interval.saturating_mul(2).min(DEFAULT_BACKOFF_CAP),
+        };
+        interval = Some(next);
+        Some(next)
+    });
+
+    let result = poll_until(sleeper, scale(wait_limit), None, intervals, || {
+        let current = value();
+        moving.set(
+            last.replace(Some(current))
+                .is_some_and(|last| last != current),
         );
-    }};
+        done(current).then_some(current)
+    })
+    .await;
+
+    // The last value is `None` if the deadline was forced to time out before any evaluation.
+    result.map_err(|error| (last.get(), error))
 }
 
-#[cfg(test)]
-mod tests {
-    use core::sync::atomic::{AtomicI32, Ordering};
-    use std::sync::Arc;
+/// Why a value waited on by [`deadline_monotonic!`] never reached its target.
+#[cfg(feature = "std")]
+#[doc(hidden)]
+#[derive(Debug, PartialEq)]
+pub enum NotMonotonic<T> {
+    /// The value went below the one before on the `poll`th evaluation, `after` the wait started.
+    Regressed {
+        previous: T,
+        value: T,
+        poll: u64,
+        after: Duration,
+    },
+    /// The deadline elapsed with the value still below the target, the last value being `None` if
+    /// it was forced to time out before any evaluation.
+    TimedOut { last: Option<T>, polls: u64 },
+}
 
-    use super::*;
+#[cfg(feature = "std")]
+#[doc(hidden)]
+pub async fn deadline_monotonic_inner<T: PartialOrd, S: Sleeper, F: Fn() -> T>(
+    sleeper: &S,
+    wait_limit: Duration,
+    value: F,
+    target: &T,
+) -> Result<T, NotMonotonic<T>> {
+    let started = sleeper.now();
+    // Without a regression, the last value is also the highest one seen.
+    let mut last = None;
+    let mut polls = 0;
 
-    #[tokio::test]
-    #[should_panic(expected = "the deadline has elapsed for condition: x == y")]
-    async fn it_times_out() {
-        let x = 1;
-        let y = 2;
+    let result = poll_until(
+        sleeper,
+        scale(wait_limit),
+        None,
+        iter::repeat(DEFAULT_POLL_INTERVAL),
+        || {
+            let current = value();
+            polls += 1;
+            match last.take() {
+                Some(previous) if current < previous => {
+                    return Some(Err(NotMonotonic::Regressed {
+                        previous,
+                        value: current,
+                        poll: polls,
+                        after: sleeper.now().saturating_duration_since(started),
+                    }));
+                }
+                _ => {}
+            }
+            if current >= *target {
+                return Some(Ok(current));
+            }
+            last = Some(current);
+            None
+        },
+    )
+    .await;
 
-        deadline!(Duration::from_millis(1), move || x == y);
+    match result {
+        Ok(result) => result,
+        Err(error) => Err(NotMonotonic::TimedOut {
+            last,
+            polls: error.polls(),
+        }),
     }
+}
 
-    #[tokio::test]
-    async fn it_waits_until_true() {
-        let x = Arc::new(AtomicI32::new(41));
-        let y = 42;
+#[cfg(feature = "std")]
+/// Evaluates each of the conditions until they have all returned `true` once, returning the
+/// indices of those which never did on timeout, along with the timeout.
+#[doc(hidden)]
+pub async fn deadline_all_inner<S: Sleeper, C: Conditions>(
+    sleeper: &S,
+    wait_limit: Duration,
+    conditions: C,
+) -> Result<(), (Vec<usize>, DeadlineError)> {
+    let mut met = vec![false; conditions.count()];
 
-        let x_clone = x.clone();
-        tokio::spawn(async move {
-            tokio::time::sleep(Duration::from_millis(5)).await;
-            x_clone.fetch_add(1, Ordering::SeqCst);
-        });
+    let result = poll_until(
+        sleeper,
+        scale(wait_limit),
+        None,
+        iter::repeat(DEFAULT_POLL_INTERVAL),
+        || {
+            for (index, met) in met.iter_mut().enumerate() {
+                // A condition which has been met isn't evaluated again.
+                *met = *met || conditions.evaluate(index);
+            }
+            met.iter().all(|met| *met).then_some(())
+        },
+    )
+    .await;
 
-        deadline!(Duration::from_millis(10), move || {
-            x.load(Ordering::Relaxed) == y
-        });
+    result.map_err(|error| {
+        let unmet = met
+            .iter()
+            .enumerate()
+            .filter_map(|(index, met)| (!met).then_some(index))
+            .collect();
+        (unmet, error)
+    })
+}
+
+#[cfg(feature = "std")]
+/// Evaluates each of the conditions in turn until one returns `true`, returning its index.
+#[doc(hidden)]
+pub async fn deadline_any_inner<S: Sleeper, C: Conditions>(
+    sleeper: &S,
+    wait_limit: Duration,
+    conditions: C,
+) -> Result<usize, DeadlineError> {
+    poll_until(
+        sleeper,
+        scale(wait_limit),
+        None,
+        iter::repeat(DEFAULT_POLL_INTERVAL),
+        || (0..conditions.count()).find(|index| conditions.evaluate(*index)),
+    )
+    .await
+}
+
+#[cfg(feature = "std")]
+#[doc(hidden)]
+pub async fn deadline_sustained_inner<S: Sleeper, F: Fn() -> bool>(
+    sleeper: &S,
+    duration: Duration,
+    condition: F,
+) -> Result<(), (Duration, u64)> {
+    // These deadlines pass by elapsing, so a forced timeout fails them as if the condition broke
+    // straight away, rather than passing them without an evaluation.
+    if forced_timeout(duration).is_err() {
+        return Err((Duration::ZERO, 0));
     }
+    let started = sleeper.now();
+    let mut polls = 0;
 
-    #[tokio::test]
-    async fn it_only_waits_as_long_as_needed() {
-        let x = Arc::new(AtomicI32::new(41));
-        let y = 42;
+    let violated = poll_until(
+        sleeper,
+        duration,
+        None,
+        iter::repeat(DEFAULT_POLL_INTERVAL),
+        || {
+            polls += 1;
+            (!condition()).then(|| sleeper.now().saturating_duration_since(started))
+        },
+    )
+    .await;
 
-        let x_clone = x.clone();
-        tokio::spawn(async move {
-            tokio::time::sleep(Duration::from_millis(5)).await;
-            x_clone.fetch_add(1, Ordering::SeqCst);
-        });
+    match violated {
+        Ok(held) => Err((held, polls)),
+        Err(_) => Ok(()),
+    }
+}
 
-        let now = std::time::Instant::now();
+/// Returns `Err((None, _))` if the condition was never true, or `Err((Some(flaps), _))` with how
+/// many times it became false again before settling.
+#[cfg(feature = "std")]
+#[doc(hidden)]
+pub async fn deadline_settle_inner<S: Sleeper, F: Fn() -> bool>(
+    sleeper: &S,
+    wait_limit: Duration,
+    settle: Duration,
+    condition: F,
+) -> Result<(), (Option<u64>, DeadlineError)> {
+    let mut true_since = None;
+    let mut flaps = None;
 
-        deadline!(Duration::from_millis(1000), move || {
-            x.load(Ordering::Relaxed) == y
-        });
+    let result = poll_until(
+        sleeper,
+        scale(wait_limit),
+        None,
+        iter::repeat(DEFAULT_POLL_INTERVAL),
+        || {
+            if !condition() {
+                if true_since.take().is_some() {
+                    *flaps.get_or_insert(0) += 1;
+                }
+                return None;
+            }
+            flaps.get_or_insert(0);
+            let now = sleeper.now();
+            let since = *true_since.get_or_insert(now);
+            (now.saturating_duration_since(since) >= settle).then_some(())
+        },
+    )
+    .await;
 
-        // Leave a bit of a gap, to avoid a flaky test.
-        assert!(now.elapsed() < Duration::from_millis(10));
+    result.map_err(|error| (flaps, error))
+}
+
+#[cfg(feature = "std")]
+#[doc(hidden)]
+pub async fn deadline_never_inner<S: Sleeper, F: Fn() -> bool>(
+    sleeper: &S,
+    wait_limit: Duration,
+    condition: F,
+) -> Result<(), (Duration, u64)> {
+    deadline_sustained_inner(sleeper, wait_limit, || !condition()).await
+}
+
+#[cfg(feature = "std")]
+/// Waits for the condition like [`deadline_inner_with_interval`], unless the token is cancelled
+/// first, in which case this returns `None`.
+#[cfg(feature = "tokio-util")]
+#[doc(hidden)]
+pub async fn deadline_cancellable_inner<S: Sleeper, R: IsReady, F: Fn() -> R>(
+    sleeper: &S,
+    wait_limit: Duration,
+    token: &tokio_util::sync::CancellationToken,
+    condition: F,
+) -> Option<Result<(), DeadlineError>> {
+    use core::{pin::pin, task::Poll};
+
+    let mut polling = pin!(deadline_inner_with_interval(
+        sleeper,
+        wait_limit,
+        DEFAULT_POLL_INTERVAL,
+        condition
+    ));
+    let mut cancelled = pin!(token.cancelled());
+
+    core::future::poll_fn(|cx| {
+        if let Poll::Ready(result) = polling.as_mut().poll(cx) {
+            return Poll::Ready(Some(result));
+        }
+        cancelled.as_mut().poll(cx).map(|()| None)
+    })
+    .await
+}
+
+#[cfg(feature = "std")]
+/// The blocking counterpart to [`poll_until`], sleeping on the current thread between evaluations.
+#[doc(hidden)]
+pub fn deadline_blocking_inner<R: IsReady, F: Fn() -> R>(
+    wait_limit: Duration,
+    condition: F,
+) -> Result<(), DeadlineError> {
+    let wait_limit = scale(wait_limit);
+    forced_timeout(wait_limit)?;
+    let started = std::time::Instant::now();
+    let mut polls = 0;
+
+    loop {
+        polls += 1;
+        if condition().is_ready() {
+            return Ok(());
+        }
+
+        let waited = started.elapsed();
+        let remaining = wait_limit.saturating_sub(waited);
+        if remaining.is_zero() {
+            return Err(DeadlineError::timed_out(wait_limit, waited, polls));
+        }
+
+        std::thread::sleep(DEFAULT_POLL_INTERVAL.min(remaining));
+    }
+}
+
+#[cfg(feature = "tokio")]
+#[doc(hidden)]
+pub async fn deadline_notify_inner<R: IsReady, F: Fn() -> R>(
+    wait_limit: Duration,
+    notify: &tokio::sync::Notify,
+    condition: F,
+) -> Result<(), DeadlineError> {
+    // The sleeps are clamped to the remaining time, so each one lasts until the next notification
+    // or the deadline.
+    poll_until(
+        &sleeper::NotifySleeper(notify),
+        scale(wait_limit),
+        None,
+        iter::repeat(Duration::MAX),
+        || condition().is_ready().then_some(()),
+    )
+    .await
+}
+
+#[cfg(feature = "tokio")]
+#[doc(hidden)]
+pub async fn deadline_spawn_blocking_inner<F: Fn() -> bool + Send + Sync + 'static>(
+    wait_limit: Duration,
+    condition: F,
+) -> Result<(), DeadlineError> {
+    let wait_limit = scale(wait_limit);
+    forced_timeout(wait_limit)?;
+    let condition = std::sync::Arc::new(condition);
+    let evaluate = || {
+        let condition = condition.clone();
+        let condition = in_context(move || condition());
+        async move {
+            match tokio::task::spawn_blocking(condition).await {
+                Ok(met) => met,
+                Err(error) => std::panic::resume_unwind(error.into_panic()),
+            }
+        }
+    };
+    let started = RuntimeSleeper.now();
+    let mut polls = 1;
+
+    // The first evaluation is always made, as it is for the other macros, so a condition that's
+    // already `true` passes a zero wait limit. It's only awaited for as long as a non-zero wait
+    // limit allows though, so a condition that never returns can't block past the deadline.
+    let met = if wait_limit.is_zero() {
+        evaluate().await
+    } else {
+        rt::timeout(wait_limit, evaluate()).await?
+    };
+    if met {
+        return Ok(());
+    }
+
+    let remaining = wait_limit.saturating_sub(RuntimeSleeper.now() - started);
+    let polling = async {
+        loop {
+            RuntimeSleeper.sleep(DEFAULT_POLL_INTERVAL).await;
+            polls += 1;
+            if evaluate().await {
+                return;
+            }
+        }
+    };
+
+    // An evaluation still running when the deadline elapses is left to finish in the background.
+    timeout(remaining, polling)
+        .await
+        .map_err(|_| DeadlineError::timed_out(wait_limit, RuntimeSleeper.now() - started, polls))
+}
+
+/// Wraps `f` to run within the calling task's context on another thread, which with the `tracing`
+/// feature is its current span and subscriber.
+#[cfg(feature = "tokio")]
+fn in_context<T>(f: impl FnOnce() -> T) -> impl FnOnce() -> T {
+    #[cfg(feature = "tracing")]
+    {
+        let dispatch = tracing::dispatcher::get_default(tracing::Dispatch::clone);
+        let span = tracing::Span::current();
+        move || tracing::dispatcher::with_default(&dispatch, || span.in_scope(f))
+    }
+
+    #[cfg(not(feature = "tracing"))]
+    {
+        f
+    }
+}
+
+#[cfg(feature = "std")]
+#[doc(hidden)]
+pub async fn deadline_poll_fn_inner<F: FnMut(&mut core::task::Context<'_>) -> Poll<()>>(
+    wait_limit: Duration,
+    mut poll: F,
+) -> Result<(), DeadlineError> {
+    let wait_limit = scale(wait_limit);
+    let started = RuntimeSleeper.now();
+    let mut polls = 0;
+
+    let polling = core::future::poll_fn(|cx| {
+        polls += 1;
+        poll(cx)
+    });
+    let result = timeout(wait_limit, polling).await;
+
+    result.map_err(|_| {
+        let waited = RuntimeSleeper.now().saturating_duration_since(started);
+        DeadlineError::timed_out(wait_limit, waited, polls)
+    })
+}
+
+/// Returns `Ok(false)` if the sender is dropped before the predicate holds.
+#[cfg(feature = "tokio")]
+#[doc(hidden)]
+pub async fn deadline_watch_inner<T, P: Fn(&T) -> bool>(
+    wait_limit: Duration,
+    rx: &mut tokio::sync::watch::Receiver<T>,
+    predicate: P,
+) -> Result<bool, DeadlineError> {
+    let wait_limit = scale(wait_limit);
+    let started = RuntimeSleeper.now();
+    let mut polls = 0;
+
+    let waiting = async {
+        loop {
+            polls += 1;
+            // The value is marked as seen, so the next change is one made after this evaluation.
+            if predicate(&rx.borrow_and_update()) {
+                return true;
+            }
+            if rx.changed().await.is_err() {
+                return false;
+            }
+        }
+    };
+    let result = timeout(wait_limit, waiting).await;
+
+    result.map_err(|_| {
+        let waited = RuntimeSleeper.now().saturating_duration_since(started);
+        DeadlineError::timed_out(wait_limit, waited, polls)
+    })
+}
+
+#[cfg(feature = "std")]
+#[doc(hidden)]
+pub async fn deadline_async_inner<F: Fn() -> Fut, Fut: Future<Output = bool>>(
+    wait_limit: Duration,
+    condition: F,
+) -> Result<(), DeadlineError> {
+    let wait_limit = scale(wait_limit);
+    let started = RuntimeSleeper.now();
+    let mut polls = 0;
+
+    let waiting = async {
+        loop {
+            polls += 1;
+            // Each evaluation's future is dropped once it resolves, before sleeping, so it can
+            // borrow anything the closure does without being `'static`, and a lock guard it holds
+            // is released in between.
+            if condition().await {
+                return;
+            }
+            RuntimeSleeper.sleep(DEFAULT_POLL_INTERVAL).await;
+        }
+    };
+    let result = timeout(wait_limit, waiting).await;
+
+    result.map_err(|_| {
+        let waited = RuntimeSleeper.now().saturating_duration_since(started);
+        DeadlineError::timed_out(wait_limit, waited, polls)
+    })
+}
+
+#[cfg(feature = "std")]
+#[doc(hidden)]
+pub async fn deadline_recv_inner<T, F: Future<Output = Option<T>>>(
+    wait_limit: Duration,
+    recv: F,
+) -> Result<Option<T>, DeadlineError> {
+    // The receive future is awaited rather than polled, so it counts as a single evaluation.
+    timeout(scale(wait_limit), recv).await
+}
+
+#[cfg(feature = "stream")]
+#[doc(hidden)]
+pub fn stream_next<S: futures_core::Stream + Unpin>(
+    stream: &mut S,
+) -> impl Future<Output = Option<S::Item>> + '_ {
+    core::future::poll_fn(|cx| core::pin::Pin::new(&mut *stream).poll_next(cx))
+}
+
+#[cfg(feature = "tokio")]
+#[doc(hidden)]
+pub async fn deadline_test_inner<F: Future>(wait_limit: Duration, test: F) -> Option<F::Output> {
+    // Not subject to `force_timeout`, which is for the assertions within the test.
+    rt::timeout(scale(wait_limit), test).await.ok()
+}
+
+/// Requires a condition closure to return `true` before the specified duration has elapsed.
+///
+/// This will panic if the provided closure doesn't evaluate to `true` before the provided duration
+/// expires. Internally, the closure is evaluated every [`DEFAULT_POLL_INTERVAL`] until it returns
+/// `true` or times out. This ensures the call is non-blocking to the async runtime. Use
+/// [`deadline_with_interval!`] to pick a different interval, or [`set_defaults`] to change how the
+/// macro polls throughout the process.
+///
+/// The closure is always evaluated at least once before the deadline is checked, however short the
+/// wait limit, so a condition that's already `true` passes even with [`Duration::ZERO`], and one
+/// that's `false` fails deterministically. This holds for every macro of the crate. With a zero
+/// wait limit, `deadline!` panics with a message saying so, since a timeout is then down to the
+/// wait limit rather than to the condition being slow.
+///
+/// The wait limit is either an integer literal taken as a number of milliseconds:
+/// `deadline!(50, ...)` waits for up to 50ms, or anything implementing `Into<Duration>`, or a
+/// reference to it, so a `&Duration` is fine too. Only literals are taken as milliseconds, so
+/// integer variables must be converted first, e.g. with [`Duration::from_millis`], or passed to
+/// [`deadline_ms!`]. The other macros take these wait limits too, except integer literals.
+///
+/// Besides a `bool`, the closure can return anything implementing [`IsReady`], e.g. an `Option`
+/// that's met once `Some`, or a readiness type of your own.
+///
+/// If the closure itself panics, the panic is caught and raised again with a message naming the
+/// condition and when it panicked. Use [`deadline_retry_panics!`] to instead treat such panics as
+/// the condition not being met yet.
+///
+/// # Examples
+///
+/// Waiting for an `AtomicI32` to be incremented to `42`:
+///
+/// ```rust
+/// # #[tokio::main]
+/// # async fn main() {
+/// #     use std::{
+/// #         sync::{
+/// #             atomic::{AtomicI32, Ordering},
+/// #             Arc,
+/// #         },
+/// #         time::Duration,
+/// #     };
+/// #
+/// #     use deadline::deadline;
+/// let x = Arc::new(AtomicI32::new(41));
+/// let y = 42;
+///
+/// let x_clone = x.clone();
+/// tokio::spawn(async move {
+///     tokio::time::sleep(Duration::from_millis(5)).await;
+///     x_clone.fetch_add(1, Ordering::SeqCst);
+/// });
+///
+/// deadline!(Duration::from_millis(10), move || {
+///     x.load(Ordering::Relaxed) == y
+/// });
+/// # }
+/// ```
+///
+/// Like [`assert!`], a custom message with optional format arguments can follow the condition. It
+/// is only formatted on failure, and prefixes the panic message:
+///
+/// ```rust,should_panic
+/// # #[tokio::main]
+/// # async fn main() {
+/// #     use std::time::Duration;
+/// #
+/// #     use deadline::deadline;
+/// let port = 8080;
+/// let server_ready = || false;
+///
+/// // Panics with "the server never listened on 8080: the deadline has elapsed for condition:
+/// // server_ready() after 10ms (waited ...)".
+/// deadline!(
+///     Duration::from_millis(10),
+///     || server_ready(),
+///     "the server never listened on {}",
+///     port
+/// );
+/// # }
+/// ```
+#[macro_export]
+macro_rules! deadline {
+    ($millis: literal, $condition: expr $(,)?) => {
+        $crate::deadline!(::core::time::Duration::from_millis($millis), $condition)
+    };
+    ($millis: literal, $condition: expr, $($arg: tt)+) => {
+        $crate::deadline!(::core::time::Duration::from_millis($millis), $condition, $($arg)+)
+    };
+    ($wait_limit: expr, $condition: expr $(,)?) => {
+        $crate::__deadline_catch_unwind!($wait_limit, $condition, false, true)
+    };
+    ($wait_limit: expr, $condition: expr, $($arg: tt)+) => {
+        $crate::__deadline_catch_unwind!($wait_limit, $condition, false, true, $($arg)+)
+    };
+}
+
+/// Like [`deadline!`], but treats panics raised by the condition closure as it returning `false`.
+///
+/// This suits conditions that panic until the state they look at is ready, e.g. by unwrapping an
+/// `Option` that isn't set yet. If the deadline elapses, the panic message includes the message of
+/// the last panic raised by the closure, if any. Note the panic hook still runs for each caught
+/// panic, so they are printed as usual.
+///
+/// # Examples
+///
+/// ```rust
+/// # #[tokio::main]
+/// # async fn main() {
+/// #     use std::{
+/// #         sync::{Arc, Mutex},
+/// #         time::Duration,
+/// #     };
+/// #
+/// #     use deadline::deadline_retry_panics;
+/// let slot = Arc::new(Mutex::new(None));
+///
+/// let slot_clone = slot.clone();
+/// tokio::spawn(async move {
+///     tokio::time::sleep(Duration::from_millis(5)).await;
+///     *slot_clone.lock().unwrap() = Some(42);
+/// });
+///
+/// deadline_retry_panics!(Duration::from_millis(100), move || {
+///     let value = *slot.lock().unwrap();
+///     value.unwrap() == 42
+/// });
+/// # }
+/// ```
+#[macro_export]
+macro_rules! deadline_retry_panics {
+    ($wait_limit: expr, $condition: expr) => {
+        $crate::__deadline_catch_unwind!($wait_limit, $condition, true, true)
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __deadline_catch_unwind {
+    ($wait_limit: expr, $condition: expr, $retry_panics: expr, $explain_zero: expr $(, $($arg: tt)+)?) => {{
+        let wait_limit = $crate::__wait_limit!($wait_limit);
+        let started = $crate::Instant::now();
+        match $crate::instrument(
+            $crate::deadline_inner_catch_unwind(
+                &$crate::RuntimeSleeper,
+                wait_limit,
+                $crate::default_poll_strategy(),
+                $retry_panics,
+                $condition,
+            ),
+            stringify!($condition),
+            wait_limit,
+        )
+        .await
+        {
+            Ok(()) => {}
+            Err((Some(panic), _)) if !$retry_panics => panic!(
+                "{}the deadline condition panicked after {:?}: {}: {}",
+                $crate::__message_prefix!($($($arg)+)?),
+                panic.after,
+                $crate::__condition_str!($condition),
+                panic.message
+            ),
+            Err((Some(panic), polls)) => {
+                let elapsed = started.elapsed();
+                $crate::__deadline_failed!(
+                    $crate::__condition_str!($condition),
+                    wait_limit,
+                    elapsed,
+                    polls,
+                    [$($($arg)+)?],
+                    "the deadline has elapsed for condition: {} after {:?} (waited {:?}), last \
+                     panicked after {:?}: {}",
+                    $crate::__condition_str!($condition),
+                    wait_limit,
+                    elapsed,
+                    panic.after,
+                    panic.message
+                )
+            }
+            Err((None, polls)) if $explain_zero && wait_limit.is_zero() => {
+                $crate::__deadline_failed!(
+                    $crate::__condition_str!($condition),
+                    wait_limit,
+                    started.elapsed(),
+                    polls,
+                    [$($($arg)+)?],
+                    "the deadline of 0 never allows waiting; condition was false at t=0: {}",
+                    $crate::__condition_str!($condition)
+                )
+            }
+            Err((None, polls)) => $crate::__deadline_failed!(
+                $crate::__condition_str!($condition),
+                wait_limit,
+                started.elapsed(),
+                polls,
+                [$($($arg)+)?]
+            ),
+        }
+    }};
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __message_prefix {
+    () => {
+        ""
+    };
+    ($($arg: tt)+) => {
+        format!("{}: ", format_args!($($arg)+))
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __message {
+    () => {
+        ::core::option::Option::<::std::string::String>::None
+    };
+    ($($arg: tt)+) => {
+        ::core::option::Option::Some(format!($($arg)+))
+    };
+}
+
+/// Panics for a deadline that wasn't met through a [`DeadlineFailure`], so that the registered
+/// formatter and [`panic_with_failures`] apply to every macro. The message passed to the macro goes
+/// in brackets, and the arguments after it, if any, word the default message instead of
+/// `deadline!`'s.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __deadline_failed {
+    (
+        $condition: expr,
+        $wait_limit: expr,
+        $elapsed: expr,
+        $polls: expr,
+        [$($arg: tt)*]
+        $(, $($wording: tt)+)?
+    ) => {{
+        let condition = $condition;
+        let message = $crate::__message!($($arg)*);
+        let failure = $crate::DeadlineFailure::new(
+            &condition,
+            message.as_deref(),
+            $wait_limit,
+            $elapsed,
+            $polls,
+        );
+        $(let failure = failure.worded(format!($($wording)+));)?
+        failure.panic()
+    }};
+}
+
+/// Like [`deadline!`], but waits up to the [`default_wait_limit`] instead of taking a wait limit.
+///
+/// This suits a test suite using the same wait limit throughout, which can then be changed in one
+/// place: with [`set_default_wait_limit`], or the `DEADLINE_DEFAULT_MS` environment variable.
+/// Otherwise, it falls back to [`DEFAULT_WAIT_LIMIT`]. The wait limit is still scaled by
+/// `DEADLINE_SCALE`.
+///
+/// # Examples
+///
+/// ```rust
+/// # #[tokio::main]
+/// # async fn main() {
+/// #     use std::time::Duration;
+/// #
+/// #     use deadline::deadline_default;
+/// deadline::set_default_wait_limit(Duration::from_millis(100));
+///
+/// let x = 42;
+/// let y = 42;
+///
+/// deadline_default!(move || x == y);
+/// # }
+/// ```
+#[macro_export]
+macro_rules! deadline_default {
+    ($condition: expr $(,)?) => {
+        $crate::deadline!($crate::default_wait_limit(), $condition)
+    };
+    ($condition: expr, $($arg: tt)+) => {
+        $crate::deadline!($crate::default_wait_limit(), $condition, $($arg)+)
+    };
+}
+
+/// Like [`deadline!`], but warns on stderr instead of panicking when the deadline elapses.
+///
+/// This is meant for a deadline too flaky to fail the tests yet, to find out how often it would
+/// while migrating a suite. The warning is the message `deadline!` would panic with, prefixed by
+/// `deadline [warn-only]` so such deadlines are easily found in the output and promoted to
+/// `deadline!` later. It's logged as well with the `log` or `tracing` features. Panics raised by
+/// the condition aren't caught.
+///
+/// # Examples
+///
+/// ```rust
+/// # #[tokio::main]
+/// # async fn main() {
+/// #     use std::time::Duration;
+/// #
+/// #     use deadline::deadline_warn;
+/// let x = 1;
+/// let y = 2;
+///
+/// // Prints "deadline [warn-only]: x is flaky: the deadline has elapsed for condition: x == y ..."
+/// deadline_warn!(Duration::from_millis(1), move || x == y, "x is flaky");
+/// # }
+/// ```
+#[macro_export]
+macro_rules! deadline_warn {
+    ($wait_limit: expr, $condition: expr $(, $($arg: tt)+)?) => {{
+        let wait_limit = $crate::__wait_limit!($wait_limit);
+        if let Err(error) = $crate::instrument(
+            $crate::deadline_future(wait_limit, $condition),
+            stringify!($condition),
+            wait_limit,
+        )
+        .await
+        {
+            let message = $crate::__message!($($($arg)+)?);
+            $crate::DeadlineFailure::new(
+                &$crate::__condition_str!($condition),
+                message.as_deref(),
+                wait_limit,
+                error.waited(),
+                error.polls(),
+            )
+            .warn();
+        }
+    }};
+}
+
+/// Like [`deadline!`], but waits for up to the time remaining in a [`Budget`] shared with other
+/// deadlines.
+///
+/// Once the budget is exhausted, the macro panics straight away without evaluating the condition,
+/// so that a helper waiting on several conditions in turn fails within its budget as a whole.
+///
+/// # Examples
+///
+/// ```rust,should_panic
+/// # #[tokio::main]
+/// # async fn main() {
+/// #     use std::time::Duration;
+/// #
+/// #     use deadline::{deadline_budget, Budget};
+/// let budget = Budget::new(Duration::from_millis(10));
+/// let x = 1;
+///
+/// deadline_budget!(budget, move || x == 1);
+/// // Waits for what's left of the 10ms, then panics.
+/// deadline_budget!(budget, move || x == 2);
+/// // Panics straight away as the budget is exhausted.
+/// deadline_budget!(budget, move || x == 1);
+/// # }
+/// ```
+#[macro_export]
+macro_rules! deadline_budget {
+    ($budget: expr, $condition: expr $(, $($arg: tt)+)?) => {{
+        let remaining = $crate::Budget::remaining(&$budget);
+        if remaining.is_zero() {
+            $crate::__deadline_failed!(
+                $crate::__condition_str!($condition),
+                remaining,
+                ::core::time::Duration::ZERO,
+                0,
+                [$($($arg)+)?],
+                "the budget was exhausted before waiting for condition: {}",
+                $crate::__condition_str!($condition)
+            );
+        }
+        $crate::deadline!(remaining, $condition $(, $($arg)+)?)
+    }};
+}
+
+/// Like [`deadline!`], but takes the wait limit as an integer number of milliseconds.
+///
+/// # Examples
+///
+/// ```rust
+/// # #[tokio::main]
+/// # async fn main() {
+/// #     use deadline::deadline_ms;
+/// let x = 42;
+/// let y = 42;
+///
+/// deadline_ms!(10, move || x == y);
+/// # }
+/// ```
+#[macro_export]
+macro_rules! deadline_ms {
+    ($millis: expr, $condition: expr) => {
+        $crate::deadline!(
+            ::core::time::Duration::from_millis(
+                u64::try_from($millis)
+                    .expect("the wait limit must be a non-negative number of milliseconds")
+            ),
+            $condition
+        )
+    };
+}
+
+/// Like [`deadline!`], but takes the wait limit as an integer number of seconds.
+///
+/// # Examples
+///
+/// ```rust
+/// # #[tokio::main]
+/// # async fn main() {
+/// #     use deadline::deadline_secs;
+/// let x = 42;
+/// let y = 42;
+///
+/// deadline_secs!(2, move || x == y);
+/// # }
+/// ```
+#[macro_export]
+macro_rules! deadline_secs {
+    ($secs: expr, $condition: expr) => {
+        $crate::deadline!(
+            ::core::time::Duration::from_secs(
+                u64::try_from($secs)
+                    .expect("the wait limit must be a non-negative number of seconds")
+            ),
+            $condition
+        )
+    };
+}
+
+/// Like [`deadline!`], but waits until an absolute [`Instant`](std::time::Instant) rather than for
+/// a duration.
+///
+/// This lets several waits share one overall budget. If the instant is already in the past, the
+/// condition is evaluated once and the macro panics if it's `false`. The instant isn't scaled by
+/// `DEADLINE_SCALE`, so each wait ends by it however the wait limits are scaled, as with
+/// [`Budget::until`]. Time is read from the backend's clock, so with the `tokio` backend the
+/// instant can also be a [`deadline::Instant`](Instant), which follows paused time.
+///
+/// # Examples
+///
+/// ```rust
+/// # #[tokio::main]
+/// # async fn main() {
+/// #     use std::time::Duration;
+/// #
+/// #     use deadline::{deadline_until, Instant};
+/// let budget = Instant::now() + Duration::from_millis(100);
+/// let x = 42;
+/// let y = 42;
+///
+/// deadline_until!(budget, move || x == y);
+/// deadline_until!(budget, move || x + 1 == y + 1);
+/// # }
+/// ```
+#[macro_export]
+macro_rules! deadline_until {
+    ($instant: expr, $condition: expr) => {
+        // A zero wait limit is down to the instant having passed here, not to a misconfiguration.
+        // The remaining time is unscaled, so scaling it as a wait limit brings it back to the
+        // instant.
+        $crate::__deadline_catch_unwind!(
+            $crate::Budget::remaining(&$crate::Budget::until(::core::convert::Into::into(
+                $instant
+            ))),
+            $condition,
+            false,
+            false
+        )
+    };
+}
+
+/// Like [`deadline!`], but evaluates to a `Result` instead of panicking when the deadline elapses.
+///
+/// This leaves the caller in control of what happens on timeout, e.g. to clean up or fall back.
+/// The [`DeadlineError`] describes the condition and its wait limit, along with how long the
+/// condition was waited on and how many times it was evaluated, so it can be logged as is.
+///
+/// # Examples
+///
+/// ```rust
+/// # #[tokio::main]
+/// # async fn main() {
+/// #     use std::time::Duration;
+/// #
+/// #     use deadline::try_deadline;
+/// let x = 1;
+/// let y = 2;
+///
+/// if let Err(error) = try_deadline!(Duration::from_millis(10), move || x == y) {
+///     // Clean up or fall back.
+///     assert!(error.polls() > 0);
+/// }
+/// # }
+/// ```
+#[macro_export]
+macro_rules! try_deadline {
+    ($wait_limit: expr, $condition: expr) => {{
+        let wait_limit = $crate::__wait_limit!($wait_limit);
+        $crate::instrument(
+            $crate::deadline_future(wait_limit, $condition),
+            stringify!($condition),
+            wait_limit,
+        )
+        .await
+        .map_err(|error| error.with_condition($crate::__condition_str!($condition)))
+    }};
+}
+
+/// Like [`try_deadline!`], but returns the error from the enclosing function on timeout, as `?`
+/// would.
+///
+/// This lets a test returning a `Result`, e.g. `Result<(), Box<dyn Error>>`, propagate the
+/// [`DeadlineError`] along with its other fallible setup. The enclosing function must return a
+/// `Result` whose error type implements `From<DeadlineError>`. Nothing happens when the condition
+/// is met in time.
+///
+/// # Examples
+///
+/// ```rust
+/// # use std::{error::Error, time::Duration};
+/// #
+/// # use deadline::deadline_q;
+/// async fn check() -> Result<(), Box<dyn Error>> {
+///     let x = 42;
+///     let y = 42;
+///
+///     deadline_q!(Duration::from_millis(10), move || x == y);
+///     Ok(())
+/// }
+/// # #[tokio::main]
+/// # async fn main() {
+/// #     check().await.unwrap();
+/// # }
+/// ```
+#[macro_export]
+macro_rules! deadline_q {
+    ($wait_limit: expr, $condition: expr) => {
+        if let ::core::result::Result::Err(error) = $crate::try_deadline!($wait_limit, $condition) {
+            return ::core::result::Result::Err(::core::convert::From::from(error));
+        }
+    };
+}
+
+/// Like [`deadline!`], but waits between evaluations of the condition according to a
+/// [`PollStrategy`].
+///
+/// This is what [`deadline_with_interval!`], [`deadline_backoff!`] and [`deadline_notify!`] are
+/// shorthands for, with the strategy picked through a value, so it can be chosen at runtime. Unlike
+/// [`deadline!`], panics raised by the closure aren't caught. As with [`deadline!`], a custom
+/// message with optional format arguments can follow the condition.
+///
+/// # Examples
+///
+/// ```rust
+/// # #[tokio::main]
+/// # async fn main() {
+/// #     use std::{
+/// #         sync::{
+/// #             atomic::{AtomicBool, Ordering},
+/// #             Arc,
+/// #         },
+/// #         time::Duration,
+/// #     };
+/// #
+/// #     use deadline::{deadline_strategy, PollStrategy};
+/// let ready = Arc::new(AtomicBool::new(false));
+///
+/// let ready_clone = ready.clone();
+/// tokio::spawn(async move {
+///     tokio::time::sleep(Duration::from_millis(20)).await;
+///     ready_clone.store(true, Ordering::SeqCst);
+/// });
+///
+/// let strategy = if std::env::var_os("CI").is_some() {
+///     PollStrategy::Interval(Duration::from_millis(10))
+/// } else {
+///     PollStrategy::Spin
+/// };
+/// deadline_strategy!(Duration::from_secs(1), strategy, move || {
+///     ready.load(Ordering::Relaxed)
+/// });
+/// # }
+/// ```
+#[macro_export]
+macro_rules! deadline_strategy {
+    ($wait_limit: expr, $strategy: expr, $condition: expr $(,)?) => {
+        $crate::__deadline_strategy!($wait_limit, $strategy, $condition)
+    };
+    ($wait_limit: expr, $strategy: expr, $condition: expr, $($arg: tt)+) => {
+        $crate::__deadline_strategy!($wait_limit, $strategy, $condition, $($arg)+)
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __deadline_strategy {
+    ($wait_limit: expr, $strategy: expr, $condition: expr $(, $($arg: tt)+)?) => {{
+        let wait_limit = $crate::__wait_limit!($wait_limit);
+        let started = $crate::Instant::now();
+        if let Err(error) = $crate::instrument(
+            $crate::deadline_strategy_inner(wait_limit, $strategy, $condition),
+            stringify!($condition),
+            wait_limit,
+        )
+        .await
+        {
+            $crate::__deadline_failed!(
+                $crate::__condition_str!($condition),
+                wait_limit,
+                started.elapsed(),
+                error.polls(),
+                [$($($arg)+)?]
+            );
+        }
+    }};
+}
+
+/// Like [`deadline!`], but waits `poll_interval` between evaluations of the condition.
+///
+/// The task sleeps for `poll_interval` each time the closure returns `false`, leaving the runtime
+/// free to make progress on whatever is supposed to satisfy the condition. With a zero interval,
+/// the task yields to the runtime between evaluations, as [`RuntimeSleeper`] does for a zero
+/// duration sleep, so other tasks and the runtime's drivers run first. To re-evaluate the closure
+/// straight away instead, pass a [`SpinSleeper`] to [`Deadline::sleeper`].
+///
+/// # Examples
+///
+/// ```rust
+/// # #[tokio::main]
+/// # async fn main() {
+/// #     use std::{
+/// #         sync::{
+/// #             atomic::{AtomicBool, Ordering},
+/// #             Arc,
+/// #         },
+/// #         time::Duration,
+/// #     };
+/// #
+/// #     use deadline::deadline_with_interval;
+/// let ready = Arc::new(AtomicBool::new(false));
+///
+/// let ready_clone = ready.clone();
+/// tokio::spawn(async move {
+///     tokio::time::sleep(Duration::from_millis(20)).await;
+///     ready_clone.store(true, Ordering::SeqCst);
+/// });
+///
+/// deadline_with_interval!(Duration::from_secs(1), Duration::from_millis(10), move || {
+///     ready.load(Ordering::Relaxed)
+/// });
+/// # }
+/// ```
+#[macro_export]
+macro_rules! deadline_with_interval {
+    ($wait_limit: expr, $poll_interval: expr, $condition: expr) => {
+        $crate::deadline_strategy!(
+            $wait_limit,
+            $crate::PollStrategy::Interval($poll_interval),
+            $condition
+        )
+    };
+}
+
+/// Like [`deadline!`], but blocks the current thread instead of awaiting, for use outside of async
+/// code.
+///
+/// The thread sleeps for [`DEFAULT_POLL_INTERVAL`] between evaluations of the closure, so this
+/// doesn't need a runtime at all. Panics raised by the closure aren't caught.
+///
+/// # Examples
+///
+/// ```rust
+/// # use std::{
+/// #     sync::{
+/// #         atomic::{AtomicBool, Ordering},
+/// #         Arc,
+/// #     },
+/// #     thread,
+/// #     time::Duration,
+/// # };
+/// #
+/// # use deadline::deadline_blocking;
+/// let ready = Arc::new(AtomicBool::new(false));
+///
+/// let ready_clone = ready.clone();
+/// thread::spawn(move || {
+///     thread::sleep(Duration::from_millis(5));
+///     ready_clone.store(true, Ordering::SeqCst);
+/// });
+///
+/// deadline_blocking!(Duration::from_millis(100), move || ready.load(Ordering::Relaxed));
+/// ```
+#[macro_export]
+macro_rules! deadline_blocking {
+    ($wait_limit: expr, $condition: expr) => {{
+        let wait_limit = $crate::__wait_limit!($wait_limit);
+        let started = ::std::time::Instant::now();
+        if let Err(error) = $crate::deadline_blocking_inner(wait_limit, $condition) {
+            $crate::__deadline_failed!(
+                $crate::__condition_str!($condition),
+                wait_limit,
+                started.elapsed(),
+                error.polls(),
+                []
+            );
+        }
+    }};
+}
+
+/// Like [`deadline!`], but evaluates the closure on the runtime's blocking thread pool, so a
+/// condition making blocking calls doesn't stall the runtime.
+///
+/// Each evaluation goes through
+/// [`tokio::task::spawn_blocking`](https://docs.rs/tokio/latest/tokio/task/fn.spawn_blocking.html),
+/// which requires the closure to be `Send`, `Sync` and `'static`. The deadline is still enforced
+/// as a whole: an evaluation running when it elapses is left to finish in the background, the
+/// first one included. The closure is evaluated at least once, and with a zero wait limit that one
+/// evaluation is awaited however long it takes. Panics raised by the closure are propagated as is.
+/// Requires the `tokio` backend.
+///
+/// With the `tracing` feature, the closure runs within the span and subscriber current where the
+/// macro is awaited, so its events are recorded as if it ran inline. The blocking threads are
+/// within the runtime's context already, e.g. for `Handle::current`. Other context of the calling
+/// task isn't carried over: tokio's `task_local!` values, which the closure can capture instead,
+/// other thread-locals, and the task's cooperative scheduling budget.
+///
+/// # Examples
+///
+/// ```rust
+/// # #[tokio::main]
+/// # async fn main() {
+/// #     use std::time::Duration;
+/// #
+/// #     use deadline::deadline_spawn_blocking;
+/// let path = std::env::temp_dir().join("deadline-spawn-blocking-example");
+///
+/// let path_clone = path.clone();
+/// tokio::spawn(async move {
+///     tokio::time::sleep(Duration::from_millis(5)).await;
+///     tokio::fs::write(path_clone, "ready").await.unwrap();
+/// });
+///
+/// deadline_spawn_blocking!(Duration::from_secs(1), move || path.exists());
+/// # }
+/// ```
+#[cfg(feature = "tokio")]
+#[macro_export]
+macro_rules! deadline_spawn_blocking {
+    ($wait_limit: expr, $condition: expr) => {{
+        let wait_limit = $crate::__wait_limit!($wait_limit);
+        let started = $crate::Instant::now();
+        if let Err(error) = $crate::instrument(
+            $crate::deadline_spawn_blocking_inner(wait_limit, $condition),
+            stringify!($condition),
+            wait_limit,
+        )
+        .await
+        {
+            $crate::__deadline_failed!(
+                $crate::__condition_str!($condition),
+                wait_limit,
+                started.elapsed(),
+                error.polls(),
+                []
+            );
+        }
+    }};
+}
+
+/// Like [`deadline!`], but takes an `FnMut` closure, so the condition can accumulate state across
+/// evaluations.
+///
+/// Unlike [`deadline!`], panics raised by the closure aren't caught.
+///
+/// # Examples
+///
+/// Waiting for a value to be seen above a threshold at least twice:
+///
+/// ```rust
+/// # #[tokio::main]
+/// # async fn main() {
+/// #     use std::{
+/// #         sync::{
+/// #             atomic::{AtomicI32, Ordering},
+/// #             Arc,
+/// #         },
+/// #         time::Duration,
+/// #     };
+/// #
+/// #     use deadline::deadline_mut;
+/// let x = Arc::new(AtomicI32::new(0));
+///
+/// let x_clone = x.clone();
+/// tokio::spawn(async move {
+///     for _ in 0..10 {
+///         tokio::time::sleep(Duration::from_millis(2)).await;
+///         x_clone.fetch_add(10, Ordering::SeqCst);
+///     }
+/// });
+///
+/// let mut seen = 0;
+/// deadline_mut!(Duration::from_secs(1), move || {
+///     if x.load(Ordering::Relaxed) > 50 {
+///         seen += 1;
+///     }
+///     seen >= 2
+/// });
+/// # }
+/// ```
+#[macro_export]
+macro_rules! deadline_mut {
+    ($wait_limit: expr, $condition: expr) => {{
+        let wait_limit = $crate::__wait_limit!($wait_limit);
+        let started = $crate::Instant::now();
+        if let Err(error) = $crate::instrument(
+            $crate::Deadline::new(wait_limit).wait($condition),
+            stringify!($condition),
+            wait_limit,
+        )
+        .await
+        {
+            $crate::__deadline_failed!(
+                $crate::__condition_str!($condition),
+                wait_limit,
+                started.elapsed(),
+                error.polls(),
+                []
+            );
+        }
+    }};
+}
+
+/// Like [`deadline_mut!`], but passes a mutable reference to a context into the condition closure
+/// on each evaluation, rather than having the closure capture it.
+///
+/// This suits state living on the test's stack, which a closure can't capture mutably and still be
+/// evaluated repeatedly without wrapping the state in a `Cell` or a `Mutex`. The context is only
+/// borrowed until the deadline is met or elapses, so it can be used again afterwards. Panics raised
+/// by the closure aren't caught.
+///
+/// # Examples
+///
+/// Collecting messages until three have arrived:
+///
+/// ```rust
+/// # #[tokio::main]
+/// # async fn main() {
+/// #     use std::{sync::mpsc, thread, time::Duration};
+/// #
+/// #     use deadline::deadline_ctx;
+/// let (tx, rx) = mpsc::channel();
+///
+/// thread::spawn(move || {
+///     for i in 1..=3 {
+///         thread::sleep(Duration::from_millis(2));
+///         tx.send(i).unwrap();
+///     }
+/// });
+///
+/// let mut received = Vec::new();
+/// deadline_ctx!(Duration::from_secs(1), &mut received, |received| {
+///     received.extend(rx.try_iter());
+///     received.len() == 3
+/// });
+/// assert_eq!(received, [1, 2, 3]);
+/// # }
+/// ```
+#[macro_export]
+macro_rules! deadline_ctx {
+    ($wait_limit: expr, $ctx: expr, $condition: expr) => {{
+        let wait_limit = $crate::__wait_limit!($wait_limit);
+        let started = $crate::Instant::now();
+        if let Err(error) = $crate::instrument(
+            $crate::deadline_ctx_inner(wait_limit, $ctx, $condition),
+            stringify!($condition),
+            wait_limit,
+        )
+        .await
+        {
+            $crate::__deadline_failed!(
+                $crate::__condition_str!($condition),
+                wait_limit,
+                started.elapsed(),
+                error.polls(),
+                []
+            );
+        }
+    }};
+}
+
+/// Like [`deadline!`], but evaluates to how long it took for the condition to be met.
+///
+/// The measurement starts before the first evaluation and ends with the one returning `true`, so
+/// it suits benchmarks of readiness latency, down to the granularity of the poll interval. It's
+/// [`DeadlineOutcome::elapsed`] from the [`Deadline`] builder. Unlike [`deadline!`], panics raised
+/// by the closure aren't caught.
+///
+/// # Examples
+///
+/// ```rust
+/// # #[tokio::main]
+/// # async fn main() {
+/// #     use std::{
+/// #         sync::{
+/// #             atomic::{AtomicBool, Ordering},
+/// #             Arc,
+/// #         },
+/// #         time::Duration,
+/// #     };
+/// #
+/// #     use deadline::deadline_timed;
+/// let ready = Arc::new(AtomicBool::new(false));
+///
+/// let ready_clone = ready.clone();
+/// tokio::spawn(async move {
+///     tokio::time::sleep(Duration::from_millis(5)).await;
+///     ready_clone.store(true, Ordering::SeqCst);
+/// });
+///
+/// let elapsed =
+///     deadline_timed!(Duration::from_millis(100), move || ready.load(Ordering::Relaxed));
+/// assert!(elapsed >= Duration::from_millis(5));
+/// # }
+/// ```
+#[macro_export]
+macro_rules! deadline_timed {
+    ($wait_limit: expr, $condition: expr) => {{
+        let wait_limit = $crate::__wait_limit!($wait_limit);
+        let started = $crate::Instant::now();
+        match $crate::instrument(
+            $crate::Deadline::new(wait_limit).wait($condition),
+            stringify!($condition),
+            wait_limit,
+        )
+        .await
+        {
+            Ok(outcome) => outcome.elapsed,
+            Err(error) => $crate::__deadline_failed!(
+                $crate::__condition_str!($condition),
+                wait_limit,
+                started.elapsed(),
+                error.polls(),
+                []
+            ),
+        }
+    }};
+}
+
+/// Requires a condition closure to return `Some` before the specified duration has elapsed, and
+/// evaluates to the contained value.
+///
+/// This avoids racing between the check and a subsequent read of the same state: the value that
+/// satisfied the deadline is the one handed back. Panics like [`deadline!`] if the closure is
+/// still returning `None` when the duration expires.
+///
+/// # Examples
+///
+/// ```rust
+/// # #[tokio::main]
+/// # async fn main() {
+/// #     use std::{
+/// #         sync::{Arc, Mutex},
+/// #         time::Duration,
+/// #     };
+/// #
+/// #     use deadline::deadline_value;
+/// let slot = Arc::new(Mutex::new(None));
+///
+/// let slot_clone = slot.clone();
+/// tokio::spawn(async move {
+///     tokio::time::sleep(Duration::from_millis(5)).await;
+///     *slot_clone.lock().unwrap() = Some(42);
+/// });
+///
+/// let x = deadline_value!(Duration::from_millis(100), move || *slot.lock().unwrap());
+/// assert_eq!(x, 42);
+/// # }
+/// ```
+#[macro_export]
+macro_rules! deadline_value {
+    ($wait_limit: expr, $condition: expr) => {{
+        let wait_limit = $crate::__wait_limit!($wait_limit);
+        let started = $crate::Instant::now();
+        match $crate::instrument(
+            $crate::deadline_value_inner(wait_limit, $condition),
+            stringify!($condition),
+            wait_limit,
+        )
+        .await
+        {
+            Ok(value) => value,
+            Err(error) => $crate::__deadline_failed!(
+                $crate::__condition_str!($condition),
+                wait_limit,
+                started.elapsed(),
+                error.polls(),
+                []
+            ),
+        }
+    }};
+}
+
+/// Requires a closure returning an `Option` to return `Some` before the specified duration has
+/// elapsed, and evaluates to the contained value.
+///
+/// This is [`deadline_value!`] under a name matching the intent of waiting for something to be
+/// set.
+///
+/// # Examples
+///
+/// ```rust
+/// # #[tokio::main]
+/// # async fn main() {
+/// #     use std::{
+/// #         sync::{Arc, OnceLock},
+/// #         time::Duration,
+/// #     };
+/// #
+/// #     use deadline::deadline_some;
+/// let address = Arc::new(OnceLock::new());
+///
+/// let address_clone = address.clone();
+/// tokio::spawn(async move {
+///     tokio::time::sleep(Duration::from_millis(5)).await;
+///     address_clone.set("127.0.0.1:8080").unwrap();
+/// });
+///
+/// let address = deadline_some!(Duration::from_millis(100), move || address.get().copied());
+/// assert_eq!(address, "127.0.0.1:8080");
+/// # }
+/// ```
+#[macro_export]
+macro_rules! deadline_some {
+    ($wait_limit: expr, $condition: expr) => {
+        $crate::deadline_value!($wait_limit, $condition)
+    };
+}
+
+/// Requires a closure returning a `Result` to return `Ok` before the specified duration has
+/// elapsed, and evaluates to the contained value.
+///
+/// An `Err` counts as the condition not being met yet. On timeout, the panic message includes the
+/// last error, which requires it to implement [`Debug`](core::fmt::Debug).
+///
+/// # Examples
+///
+/// ```rust
+/// # #[tokio::main]
+/// # async fn main() {
+/// #     use std::{
+/// #         sync::{Arc, Mutex},
+/// #         time::Duration,
+/// #     };
+/// #
+/// #     use deadline::deadline_ok;
+/// let config = Arc::new(Mutex::new(String::new()));
+///
+/// let config_clone = config.clone();
+/// tokio::spawn(async move {
+///     tokio::time::sleep(Duration::from_millis(5)).await;
+///     *config_clone.lock().unwrap() = "42".to_owned();
+/// });
+///
+/// let x: u32 = deadline_ok!(Duration::from_millis(100), move || config.lock().unwrap().parse());
+/// assert_eq!(x, 42);
+/// # }
+/// ```
+#[macro_export]
+macro_rules! deadline_ok {
+    ($wait_limit: expr, $condition: expr) => {{
+        let wait_limit = $crate::__wait_limit!($wait_limit);
+        let started = $crate::Instant::now();
+        match $crate::instrument(
+            $crate::deadline_ok_inner(&$crate::RuntimeSleeper, wait_limit, $condition),
+            stringify!($condition),
+            wait_limit,
+        )
+        .await
+        {
+            Ok(value) => value,
+            Err((None, error)) => $crate::__deadline_failed!(
+                $crate::__condition_str!($condition),
+                wait_limit,
+                started.elapsed(),
+                error.polls(),
+                []
+            ),
+            Err((Some(last), error)) => {
+                let elapsed = started.elapsed();
+                $crate::__deadline_failed!(
+                    $crate::__condition_str!($condition),
+                    wait_limit,
+                    elapsed,
+                    error.polls(),
+                    [],
+                    "the deadline has elapsed for condition: {} (last error = {:?}) after {:?} \
+                     (waited {:?})",
+                    $crate::__condition_str!($condition),
+                    last,
+                    wait_limit,
+                    elapsed
+                )
+            }
+        }
+    }};
+}
+
+/// Requires a closure returning a `Poll<Result>` to return `Poll::Ready(Ok)` before the specified
+/// duration has elapsed, failing fast on `Poll::Ready(Err)`, and evaluates to the contained value.
+///
+/// `Poll::Pending` counts as the condition not being met yet, while an error means it never will
+/// be, e.g. because the task it was waiting on crashed: the macro then panics straight away with
+/// the error, which requires it to implement [`Debug`](core::fmt::Debug), instead of waiting out
+/// the deadline. Not to be confused with [`try_deadline!`], which returns an error on timeout
+/// instead of panicking.
+///
+/// # Examples
+///
+/// ```rust
+/// # #[tokio::main]
+/// # async fn main() {
+/// #     use std::{
+/// #         sync::{Arc, Mutex},
+/// #         task::Poll,
+/// #         time::Duration,
+/// #     };
+/// #
+/// #     use deadline::deadline_try;
+/// let status = Arc::new(Mutex::new(Poll::Pending));
+///
+/// let status_clone = status.clone();
+/// tokio::spawn(async move {
+///     tokio::time::sleep(Duration::from_millis(5)).await;
+///     *status_clone.lock().unwrap() = Poll::Ready(Ok::<_, String>(42));
+/// });
+///
+/// let x = deadline_try!(Duration::from_millis(100), move || status.lock().unwrap().clone());
+/// assert_eq!(x, 42);
+/// # }
+/// ```
+#[macro_export]
+macro_rules! deadline_try {
+    ($wait_limit: expr, $condition: expr) => {{
+        let wait_limit = $crate::__wait_limit!($wait_limit);
+        let started = $crate::Instant::now();
+        match $crate::instrument(
+            $crate::deadline_try_inner(&$crate::RuntimeSleeper, wait_limit, $condition),
+            stringify!($condition),
+            wait_limit,
+        )
+        .await
+        {
+            Ok(value) => value,
+            Err(Ok(error)) => panic!(
+                "the deadline condition failed after {:?}: {}: {:?}",
+                started.elapsed(),
+                $crate::__condition_str!($condition),
+                error
+            ),
+            Err(Err(error)) => $crate::__deadline_failed!(
+                $crate::__condition_str!($condition),
+                wait_limit,
+                started.elapsed(),
+                error.polls(),
+                []
+            ),
+        }
+    }};
+}
+
+/// Requires a condition closure to return `true` before the specified duration has elapsed, then
+/// runs a follow-up closure and evaluates to its result.
+///
+/// The follow-up runs right after the evaluation that returned `true`, within the same poll and
+/// without yielding to the runtime, so nothing else on the same task can change the state both
+/// closures look at in between. This makes the follow-up the place to assert more properties of
+/// that state. Panics like [`deadline!`] on timeout, in which case the follow-up never runs.
+///
+/// # Examples
+///
+/// ```rust
+/// # #[tokio::main]
+/// # async fn main() {
+/// #     use std::{
+/// #         sync::{Arc, Mutex},
+/// #         time::Duration,
+/// #     };
+/// #
+/// #     use deadline::deadline_then;
+/// let queue = Arc::new(Mutex::new(Vec::new()));
+///
+/// let queue_clone = queue.clone();
+/// tokio::spawn(async move {
+///     tokio::time::sleep(Duration::from_millis(5)).await;
+///     queue_clone.lock().unwrap().push(42);
+/// });
+///
+/// deadline_then!(
+///     Duration::from_millis(100),
+///     || !queue.lock().unwrap().is_empty(),
+///     |()| assert_eq!(*queue.lock().unwrap(), [42])
+/// );
+/// # }
+/// ```
+#[macro_export]
+macro_rules! deadline_then {
+    ($wait_limit: expr, $condition: expr, $then: expr $(,)?) => {{
+        let wait_limit = $crate::__wait_limit!($wait_limit);
+        let started = $crate::Instant::now();
+        match $crate::instrument(
+            $crate::deadline_then_inner(wait_limit, $condition, $then),
+            stringify!($condition),
+            wait_limit,
+        )
+        .await
+        {
+            Ok(value) => value,
+            Err(error) => $crate::__deadline_failed!(
+                $crate::__condition_str!($condition),
+                wait_limit,
+                started.elapsed(),
+                error.polls(),
+                []
+            ),
+        }
+    }};
+}
+
+/// Requires the value returned by a closure to equal `expected` before the specified duration has
+/// elapsed.
+///
+/// This is the [`assert_eq!`] of deadlines: on timeout, the panic message includes the last value
+/// returned by the closure alongside the expected value, which requires both to implement
+/// [`Debug`](core::fmt::Debug). With the `diff` feature enabled, the values are pretty-printed and
+/// the message ends with a line diff of the two instead, which reads better for large structs:
+///
+/// ```text
+/// diff < left / right >:
+///  Config {
+///      name: "server",
+/// <    port: 8080,
+/// >    port: 8081,
+///  }
+/// ```
+///
+/// # Examples
+///
+/// ```rust
+/// # #[tokio::main]
+/// # async fn main() {
+/// #     use std::{
+/// #         sync::{
+/// #             atomic::{AtomicI32, Ordering},
+/// #             Arc,
+/// #         },
+/// #         time::Duration,
+/// #     };
+/// #
+/// #     use deadline::deadline_eq;
+/// let x = Arc::new(AtomicI32::new(41));
+///
+/// let x_clone = x.clone();
+/// tokio::spawn(async move {
+///     tokio::time::sleep(Duration::from_millis(5)).await;
+///     x_clone.fetch_add(1, Ordering::SeqCst);
+/// });
+///
+/// deadline_eq!(Duration::from_millis(100), move || x.load(Ordering::Relaxed), 42);
+/// # }
+/// ```
+#[macro_export]
+macro_rules! deadline_eq {
+    ($wait_limit: expr, $actual: expr, $expected: expr) => {{
+        let wait_limit = $crate::__wait_limit!($wait_limit);
+        let expected = $expected;
+        let started = $crate::Instant::now();
+        match $crate::instrument(
+            $crate::deadline_eq_inner(&$crate::RuntimeSleeper, wait_limit, $actual, &expected),
+            stringify!($actual),
+            wait_limit,
+        )
+        .await
+        {
+            Ok(()) => {}
+            Err((None, error)) => $crate::__deadline_failed!(
+                format!(
+                    "{} == {}",
+                    $crate::__condition_str!($actual),
+                    stringify!($expected)
+                ),
+                wait_limit,
+                started.elapsed(),
+                error.polls(),
+                []
+            ),
+            Err((Some(last), error)) => {
+                let mismatch = $crate::mismatch(&last, &expected);
+                let elapsed = started.elapsed();
+                $crate::__deadline_failed!(
+                    format!(
+                        "{} == {}",
+                        $crate::__condition_str!($actual),
+                        stringify!($expected)
+                    ),
+                    wait_limit,
+                    elapsed,
+                    error.polls(),
+                    [],
+                    "the deadline has elapsed for condition: {} == {}{} after {:?} (waited \
+                     {:?}){}",
+                    $crate::__condition_str!($actual),
+                    stringify!($expected),
+                    mismatch.inline,
+                    wait_limit,
+                    elapsed,
+                    mismatch.trailing
+                )
+            }
+        }
+    }};
+}
+
+/// Requires the values returned by two closures to be equal before the specified duration has
+/// elapsed.
+///
+/// This is [`deadline_eq!`] with a moving target, e.g. a follower's committed index catching up
+/// with the leader's: both closures are evaluated each poll, left first. On timeout, the panic
+/// message includes the last values returned by each, formatted as [`deadline_eq!`] does, including
+/// the line diff with the `diff` feature.
+///
+/// # Examples
+///
+/// ```rust
+/// # #[tokio::main]
+/// # async fn main() {
+/// #     use std::{
+/// #         sync::{
+/// #             atomic::{AtomicU64, Ordering},
+/// #             Arc,
+/// #         },
+/// #         time::Duration,
+/// #     };
+/// #
+/// #     use deadline::deadline_eq_fn;
+/// let leader = Arc::new(AtomicU64::new(10));
+/// let follower = Arc::new(AtomicU64::new(7));
+///
+/// let (leader_clone, follower_clone) = (leader.clone(), follower.clone());
+/// tokio::spawn(async move {
+///     tokio::time::sleep(Duration::from_millis(1)).await;
+///     leader_clone.store(12, Ordering::SeqCst);
+///     tokio::time::sleep(Duration::from_millis(5)).await;
+///     follower_clone.store(12, Ordering::SeqCst);
+/// });
+///
+/// deadline_eq_fn!(
+///     Duration::from_millis(100),
+///     move || follower.load(Ordering::Relaxed),
+///     move || leader.load(Ordering::Relaxed)
+/// );
+/// # }
+/// ```
+#[macro_export]
+macro_rules! deadline_eq_fn {
+    ($wait_limit: expr, $left: expr, $right: expr) => {{
+        let wait_limit = $crate::__wait_limit!($wait_limit);
+        let (left, right) = ($left, $right);
+        let started = $crate::Instant::now();
+        match $crate::instrument(
+            $crate::deadline_cmp_inner(
+                &$crate::RuntimeSleeper,
+                wait_limit,
+                || (left(), right()),
+                |(left, right)| left == right,
+            ),
+            stringify!($left == $right),
+            wait_limit,
+        )
+        .await
+        {
+            Ok(()) => {}
+            Err((None, error)) => $crate::__deadline_failed!(
+                format!(
+                    "{} == {}",
+                    $crate::__condition_str!($left),
+                    $crate::__condition_str!($right)
+                ),
+                wait_limit,
+                started.elapsed(),
+                error.polls(),
+                []
+            ),
+            Err((Some((left, right)), error)) => {
+                let mismatch = $crate::mismatch(&left, &right);
+                let elapsed = started.elapsed();
+                $crate::__deadline_failed!(
+                    format!(
+                        "{} == {}",
+                        $crate::__condition_str!($left),
+                        $crate::__condition_str!($right)
+                    ),
+                    wait_limit,
+                    elapsed,
+                    error.polls(),
+                    [],
+                    "the deadline has elapsed for condition: {} == {}{} after {:?} (waited \
+                     {:?}){}",
+                    $crate::__condition_str!($left),
+                    $crate::__condition_str!($right),
+                    mismatch.inline,
+                    wait_limit,
+                    elapsed,
+                    mismatch.trailing
+                )
+            }
+        }
+    }};
+}
+
+/// Requires an atomic to hold `expected` before the specified duration has elapsed.
+///
+/// This is a shorthand for [`deadline_eq!`] with a closure loading the atomic, for any of the
+/// standard atomic types, e.g. [`AtomicUsize`](core::sync::atomic::AtomicUsize) or
+/// [`AtomicBool`](core::sync::atomic::AtomicBool). The atomic is loaded with
+/// [`Relaxed`](core::sync::atomic::Ordering::Relaxed) ordering unless another is passed as a
+/// fourth argument. On timeout, the panic message includes the last loaded value alongside the
+/// expected value.
+///
+/// # Examples
+///
+/// ```rust
+/// # #[tokio::main]
+/// # async fn main() {
+/// #     use std::{
+/// #         sync::{
+/// #             atomic::{AtomicUsize, Ordering},
+/// #             Arc,
+/// #         },
+/// #         time::Duration,
+/// #     };
+/// #
+/// #     use deadline::deadline_atomic;
+/// let x = Arc::new(AtomicUsize::new(41));
+///
+/// let x_clone = x.clone();
+/// tokio::spawn(async move {
+///     tokio::time::sleep(Duration::from_millis(5)).await;
+///     x_clone.fetch_add(1, Ordering::SeqCst);
+/// });
+///
+/// deadline_atomic!(Duration::from_millis(100), &x, 42);
+/// deadline_atomic!(Duration::from_millis(100), &x, 42, Ordering::Acquire);
+/// # }
+/// ```
+#[macro_export]
+macro_rules! deadline_atomic {
+    ($wait_limit: expr, $atomic: expr, $expected: expr) => {
+        $crate::deadline_atomic!(
+            $wait_limit,
+            $atomic,
+            $expected,
+            ::core::sync::atomic::Ordering::Relaxed
+        )
+    };
+    ($wait_limit: expr, $atomic: expr, $expected: expr, $ordering: expr) => {{
+        let wait_limit = $crate::__wait_limit!($wait_limit);
+        let atomic = $atomic;
+        let ordering = $ordering;
+        let expected = $expected;
+        let started = $crate::Instant::now();
+        match $crate::instrument(
+            $crate::deadline_eq_inner(
+                &$crate::RuntimeSleeper,
+                wait_limit,
+                || atomic.load(ordering),
+                &expected,
+            ),
+            stringify!($atomic),
+            wait_limit,
+        )
+        .await
+        {
+            Ok(()) => {}
+            Err((None, error)) => $crate::__deadline_failed!(
+                format!("{} == {}", stringify!($atomic), stringify!($expected)),
+                wait_limit,
+                started.elapsed(),
+                error.polls(),
+                []
+            ),
+            Err((Some(last), error)) => {
+                let elapsed = started.elapsed();
+                $crate::__deadline_failed!(
+                    format!("{} == {}", stringify!($atomic), stringify!($expected)),
+                    wait_limit,
+                    elapsed,
+                    error.polls(),
+                    [],
+                    "the deadline has elapsed for condition: {} == {} (left = {:?}, right = {:?}) \
+                     after {:?} (waited {:?})",
+                    stringify!($atomic),
+                    stringify!($expected),
+                    last,
+                    expected,
+                    wait_limit,
+                    elapsed
+                )
+            }
+        }
+    }};
+}
+
+/// Requires the value returned by a closure to be greater than or equal to `target` before the
+/// specified duration has elapsed.
+///
+/// Unlike [`deadline_eq!`], this can't miss a quantity going past the target between two
+/// evaluations, which makes it the better fit for counters and other monotonic values. On timeout,
+/// the panic message includes the last value returned by the closure alongside the target, which
+/// requires both to implement [`Debug`](core::fmt::Debug).
+///
+/// # Examples
+///
+/// ```rust
+/// # #[tokio::main]
+/// # async fn main() {
+/// #     use std::{
+/// #         sync::{
+/// #             atomic::{AtomicI32, Ordering},
+/// #             Arc,
+/// #         },
+/// #         time::Duration,
+/// #     };
+/// #
+/// #     use deadline::deadline_ge;
+/// let x = Arc::new(AtomicI32::new(40));
+///
+/// let x_clone = x.clone();
+/// tokio::spawn(async move {
+///     for _ in 0..5 {
+///         tokio::time::sleep(Duration::from_millis(1)).await;
+///         x_clone.fetch_add(1, Ordering::SeqCst);
+///     }
+/// });
+///
+/// deadline_ge!(Duration::from_millis(100), move || x.load(Ordering::Relaxed), 42);
+/// # }
+/// ```
+#[macro_export]
+macro_rules! deadline_ge {
+    ($wait_limit: expr, $actual: expr, $target: expr) => {
+        $crate::__deadline_cmp!($wait_limit, $actual, >=, $target)
+    };
+}
+
+/// Like [`deadline_ge!`], but requires the value to be greater than `target`.
+///
+/// # Examples
+///
+/// ```rust
+/// # #[tokio::main]
+/// # async fn main() {
+/// #     use std::{
+/// #         sync::{
+/// #             atomic::{AtomicI32, Ordering},
+/// #             Arc,
+/// #         },
+/// #         time::Duration,
+/// #     };
+/// #
+/// #     use deadline::deadline_gt;
+/// let x = Arc::new(AtomicI32::new(40));
+///
+/// let x_clone = x.clone();
+/// tokio::spawn(async move {
+///     for _ in 0..5 {
+///         tokio::time::sleep(Duration::from_millis(1)).await;
+///         x_clone.fetch_add(1, Ordering::SeqCst);
+///     }
+/// });
+///
+/// deadline_gt!(Duration::from_millis(100), move || x.load(Ordering::Relaxed), 42);
+/// # }
+/// ```
+#[macro_export]
+macro_rules! deadline_gt {
+    ($wait_limit: expr, $actual: expr, $target: expr) => {
+        $crate::__deadline_cmp!($wait_limit, $actual, >, $target)
+    };
+}
+
+/// Like [`deadline_ge!`], but requires the value to be less than or equal to `target`.
+///
+/// # Examples
+///
+/// ```rust
+/// # #[tokio::main]
+/// # async fn main() {
+/// #     use std::{
+/// #         sync::{
+/// #             atomic::{AtomicI32, Ordering},
+/// #             Arc,
+/// #         },
+/// #         time::Duration,
+/// #     };
+/// #
+/// #     use deadline::deadline_le;
+/// let x = Arc::new(AtomicI32::new(44));
+///
+/// let x_clone = x.clone();
+/// tokio::spawn(async move {
+///     for _ in 0..5 {
+///         tokio::time::sleep(Duration::from_millis(1)).await;
+///         x_clone.fetch_sub(1, Ordering::SeqCst);
+///     }
+/// });
+///
+/// deadline_le!(Duration::from_millis(100), move || x.load(Ordering::Relaxed), 42);
+/// # }
+/// ```
+#[macro_export]
+macro_rules! deadline_le {
+    ($wait_limit: expr, $actual: expr, $target: expr) => {
+        $crate::__deadline_cmp!($wait_limit, $actual, <=, $target)
+    };
+}
+
+/// Like [`deadline_ge!`], but requires the value to be less than `target`.
+///
+/// # Examples
+///
+/// ```rust
+/// # #[tokio::main]
+/// # async fn main() {
+/// #     use std::{
+/// #         sync::{
+/// #             atomic::{AtomicI32, Ordering},
+/// #             Arc,
+/// #         },
+/// #         time::Duration,
+/// #     };
+/// #
+/// #     use deadline::deadline_lt;
+/// let x = Arc::new(AtomicI32::new(44));
+///
+/// let x_clone = x.clone();
+/// tokio::spawn(async move {
+///     for _ in 0..5 {
+///         tokio::time::sleep(Duration::from_millis(1)).await;
+///         x_clone.fetch_sub(1, Ordering::SeqCst);
+///     }
+/// });
+///
+/// deadline_lt!(Duration::from_millis(100), move || x.load(Ordering::Relaxed), 42);
+/// # }
+/// ```
+#[macro_export]
+macro_rules! deadline_lt {
+    ($wait_limit: expr, $actual: expr, $target: expr) => {
+        $crate::__deadline_cmp!($wait_limit, $actual, <, $target)
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __deadline_cmp {
+    ($wait_limit: expr, $actual: expr, $op: tt, $target: expr) => {{
+        let wait_limit = $crate::__wait_limit!($wait_limit);
+        let target = $target;
+        let started = $crate::Instant::now();
+        match $crate::instrument(
+            $crate::deadline_cmp_inner(&$crate::RuntimeSleeper, wait_limit, $actual, |value| {
+                *value $op target
+            }),
+            stringify!($actual),
+            wait_limit,
+        )
+        .await
+        {
+            Ok(()) => {}
+            Err((None, error)) => $crate::__deadline_failed!(
+                format!(
+                    "{} {} {}",
+                    $crate::__condition_str!($actual),
+                    stringify!($op),
+                    stringify!($target)
+                ),
+                wait_limit,
+                started.elapsed(),
+                error.polls(),
+                []
+            ),
+            Err((Some(last), error)) => {
+                let elapsed = started.elapsed();
+                $crate::__deadline_failed!(
+                    format!(
+                        "{} {} {}",
+                        $crate::__condition_str!($actual),
+                        stringify!($op),
+                        stringify!($target)
+                    ),
+                    wait_limit,
+                    elapsed,
+                    error.polls(),
+                    [],
+                    "the deadline has elapsed for condition: {} {} {} (left = {:?}, right = {:?}) \
+                     after {:?} (waited {:?})",
+                    $crate::__condition_str!($actual),
+                    stringify!($op),
+                    stringify!($target),
+                    last,
+                    target,
+                    wait_limit,
+                    elapsed
+                )
+            }
+        }
+    }};
+}
+
+/// Requires a closure returning an `f64` to come within `epsilon` of a target value before the
+/// specified duration has elapsed.
+///
+/// The closure is polled until `(value - target).abs() <= epsilon`, which a NaN value, target or
+/// epsilon never satisfies. On timeout, the panic message includes the last observed value
+/// alongside the target and the epsilon.
+///
+/// # Examples
+///
+/// ```rust
+/// # #[tokio::main]
+/// # async fn main() {
+/// #     use std::{
+/// #         sync::{Arc, Mutex},
+/// #         time::Duration,
+/// #     };
+/// #
+/// #     use deadline::deadline_approx;
+/// let temperature = Arc::new(Mutex::new(20.0));
+///
+/// let temperature_clone = temperature.clone();
+/// tokio::spawn(async move {
+///     tokio::time::sleep(Duration::from_millis(5)).await;
+///     *temperature_clone.lock().unwrap() = 36.6;
+/// });
+///
+/// deadline_approx!(
+///     Duration::from_millis(100),
+///     move || *temperature.lock().unwrap(),
+///     36.6,
+///     0.1
+/// );
+/// # }
+/// ```
+#[macro_export]
+macro_rules! deadline_approx {
+    ($wait_limit: expr, $actual: expr, $target: expr, $epsilon: expr) => {{
+        let wait_limit = $crate::__wait_limit!($wait_limit);
+        let target: f64 = $target;
+        let epsilon: f64 = $epsilon;
+        let started = $crate::Instant::now();
+        match $crate::instrument(
+            $crate::deadline_approx_inner(
+                &$crate::RuntimeSleeper,
+                wait_limit,
+                $actual,
+                target,
+                epsilon,
+            ),
+            stringify!($actual),
+            wait_limit,
+        )
+        .await
+        {
+            Ok(()) => {}
+            Err((None, error)) => $crate::__deadline_failed!(
+                format!(
+                    "{} ~= {}",
+                    $crate::__condition_str!($actual),
+                    stringify!($target)
+                ),
+                wait_limit,
+                started.elapsed(),
+                error.polls(),
+                []
+            ),
+            Err((Some(last), error)) => {
+                let elapsed = started.elapsed();
+                $crate::__deadline_failed!(
+                    format!(
+                        "{} ~= {}",
+                        $crate::__condition_str!($actual),
+                        stringify!($target)
+                    ),
+                    wait_limit,
+                    elapsed,
+                    error.polls(),
+                    [],
+                    "the deadline has elapsed for condition: {} ~= {} (last = {:?}, target = \
+                     {:?}, epsilon = {:?}) after {:?} (waited {:?})",
+                    $crate::__condition_str!($actual),
+                    stringify!($target),
+                    last,
+                    target,
+                    epsilon,
+                    wait_limit,
+                    elapsed
+                )
+            }
+        }
+    }};
+}
+
+/// Requires the length returned by a closure to equal `expected` before the specified duration has
+/// elapsed.
+///
+/// This is [`deadline_eq!`] for the common case of waiting for a collection to fill up, e.g. a
+/// `Vec` behind a lock. The expected length can be preceded by `>=`, `<=`, `>` or `<` to wait for a
+/// length in a range instead, e.g. `>= 3` for at least three entries. On timeout, the panic message
+/// includes the last observed length.
+///
+/// # Examples
+///
+/// ```rust
+/// # #[tokio::main]
+/// # async fn main() {
+/// #     use std::{
+/// #         sync::{Arc, Mutex},
+/// #         time::Duration,
+/// #     };
+/// #
+/// #     use deadline::deadline_len;
+/// let events = Arc::new(Mutex::new(Vec::new()));
+///
+/// let events_clone = events.clone();
+/// tokio::spawn(async move {
+///     for event in 0..5 {
+///         tokio::time::sleep(Duration::from_millis(1)).await;
+///         events_clone.lock().unwrap().push(event);
+///     }
+/// });
+///
+/// deadline_len!(Duration::from_millis(100), || events.lock().unwrap().len(), >= 3);
+/// deadline_len!(Duration::from_millis(100), || events.lock().unwrap().len(), 5);
+/// # }
+/// ```
+#[macro_export]
+macro_rules! deadline_len {
+    ($wait_limit: expr, $len: expr, >= $expected: expr) => {
+        $crate::__deadline_len!($wait_limit, $len, >=, $expected)
+    };
+    ($wait_limit: expr, $len: expr, <= $expected: expr) => {
+        $crate::__deadline_len!($wait_limit, $len, <=, $expected)
+    };
+    ($wait_limit: expr, $len: expr, > $expected: expr) => {
+        $crate::__deadline_len!($wait_limit, $len, >, $expected)
+    };
+    ($wait_limit: expr, $len: expr, < $expected: expr) => {
+        $crate::__deadline_len!($wait_limit, $len, <, $expected)
+    };
+    ($wait_limit: expr, $len: expr, $expected: expr) => {
+        $crate::__deadline_len!($wait_limit, $len, ==, $expected)
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __deadline_len {
+    ($wait_limit: expr, $len: expr, $op: tt, $expected: expr) => {{
+        let wait_limit = $crate::__wait_limit!($wait_limit);
+        let expected: usize = $expected;
+        let started = $crate::Instant::now();
+        match $crate::instrument(
+            $crate::deadline_cmp_inner(&$crate::RuntimeSleeper, wait_limit, $len, |len: &usize| {
+                *len $op expected
+            }),
+            stringify!($len),
+            wait_limit,
+        )
+        .await
+        {
+            Ok(()) => {}
+            Err((None, error)) => $crate::__deadline_failed!(
+                format!(
+                    "{} {} {}",
+                    $crate::__condition_str!($len),
+                    stringify!($op),
+                    stringify!($expected)
+                ),
+                wait_limit,
+                started.elapsed(),
+                error.polls(),
+                []
+            ),
+            Err((Some(last), error)) => {
+                let elapsed = started.elapsed();
+                $crate::__deadline_failed!(
+                    format!(
+                        "{} {} {}",
+                        $crate::__condition_str!($len),
+                        stringify!($op),
+                        stringify!($expected)
+                    ),
+                    wait_limit,
+                    elapsed,
+                    error.polls(),
+                    [],
+                    "the deadline has elapsed for condition: {} {} {} (len = {}, expected {} {}) \
+                     after {:?} (waited {:?})",
+                    $crate::__condition_str!($len),
+                    stringify!($op),
+                    stringify!($expected),
+                    last,
+                    stringify!($op),
+                    expected,
+                    wait_limit,
+                    elapsed
+                )
+            }
+        }
+    }};
+}
+
+/// Like [`deadline!`], but doubles the interval between evaluations each time the condition is
+/// `false`.
+///
+/// The interval starts at [`DEFAULT_BACKOFF_START`] and is capped at [`DEFAULT_BACKOFF_CAP`],
+/// unless both are passed as trailing arguments. This keeps latency low for conditions that are
+/// met quickly while not wasting cycles on ones that take a while. The interval is also clamped
+/// to the time remaining, so the condition is evaluated one last time as the deadline elapses.
+///
+/// # Examples
+///
+/// ```rust
+/// # #[tokio::main]
+/// # async fn main() {
+/// #     use std::{
+/// #         sync::{
+/// #             atomic::{AtomicBool, Ordering},
+/// #             Arc,
+/// #         },
+/// #         time::Duration,
+/// #     };
+/// #
+/// #     use deadline::deadline_backoff;
+/// let synced = Arc::new(AtomicBool::new(false));
+///
+/// let synced_clone = synced.clone();
+/// tokio::spawn(async move {
+///     tokio::time::sleep(Duration::from_millis(50)).await;
+///     synced_clone.store(true, Ordering::SeqCst);
+/// });
+///
+/// deadline_backoff!(
+///     Duration::from_secs(1),
+///     move || synced.load(Ordering::Relaxed),
+///     Duration::from_millis(2),
+///     Duration::from_millis(20)
+/// );
+/// # }
+/// ```
+#[macro_export]
+macro_rules! deadline_backoff {
+    ($wait_limit: expr, $condition: expr) => {
+        $crate::deadline_backoff!(
+            $wait_limit,
+            $condition,
+            $crate::DEFAULT_BACKOFF_START,
+            $crate::DEFAULT_BACKOFF_CAP
+        )
+    };
+    ($wait_limit: expr, $condition: expr, $start: expr, $cap: expr) => {
+        $crate::deadline_strategy!(
+            $wait_limit,
+            $crate::PollStrategy::Backoff {
+                start: $start,
+                cap: $cap,
+            },
+            $condition
+        )
+    };
+}
+
+/// Retries a condition closure with capped exponential backoff until it returns `true`, and
+/// evaluates to the number of attempts and how long they took, as a `(u64, Duration)`.
+///
+/// This suits flaky external dependencies, where each evaluation is an attempt: unlike
+/// [`deadline_backoff!`], it stops at whichever comes first of `max_elapsed` and `max_attempts`,
+/// and the panic message states both how many attempts were made and how long they ran. The
+/// backoff goes from [`DEFAULT_BACKOFF_START`] to [`DEFAULT_BACKOFF_CAP`] unless given.
+///
+/// # Examples
+///
+/// ```rust
+/// # #[tokio::main]
+/// # async fn main() {
+/// #     use std::time::Duration;
+/// #
+/// #     use deadline::deadline_retry;
+/// let mut failures = 2;
+///
+/// let (attempts, _elapsed) = deadline_retry!(Duration::from_secs(1), 5, move || {
+///     // E.g. a request to a service still starting up.
+///     failures -= 1;
+///     failures < 0
+/// });
+/// assert_eq!(attempts, 3);
+/// # }
+/// ```
+#[macro_export]
+macro_rules! deadline_retry {
+    ($max_elapsed: expr, $max_attempts: expr, $condition: expr) => {
+        $crate::deadline_retry!(
+            $max_elapsed,
+            $max_attempts,
+            $condition,
+            $crate::DEFAULT_BACKOFF_START,
+            $crate::DEFAULT_BACKOFF_CAP
+        )
+    };
+    ($max_elapsed: expr, $max_attempts: expr, $condition: expr, $start: expr, $cap: expr) => {{
+        let max_elapsed = $crate::__wait_limit!($max_elapsed);
+        let max_attempts = $max_attempts;
+        match $crate::instrument(
+            $crate::Deadline::new(max_elapsed)
+                .backoff($start, $cap)
+                .max_polls(max_attempts)
+                .wait($condition),
+            stringify!($condition),
+            max_elapsed,
+        )
+        .await
+        {
+            Ok(outcome) => (outcome.polls, outcome.elapsed),
+            Err(error) => $crate::__deadline_failed!(
+                $crate::__condition_str!($condition),
+                max_elapsed,
+                error.waited(),
+                error.polls(),
+                [],
+                "the condition was still false after {} attempts and {:?}: {} (at most {} \
+                 attempts within {:?})",
+                error.polls(),
+                error.waited(),
+                $crate::__condition_str!($condition),
+                max_attempts,
+                max_elapsed
+            ),
+        }
+    }};
+}
+
+/// Requires a closure returning a progress counter to reach a value satisfying a predicate before
+/// the specified duration has elapsed, and evaluates to that value.
+///
+/// The interval between evaluations adapts to the counter: it halves, down to
+/// [`DEFAULT_BACKOFF_START`], while the value is changing, and doubles, up to
+/// [`DEFAULT_BACKOFF_CAP`], while it's stalled. This suits throughput-style conditions, e.g. the
+/// number of bytes processed. On timeout, the panic message includes the last value and the number
+/// of evaluations.
+///
+/// # Examples
+///
+/// ```rust
+/// # #[tokio::main]
+/// # async fn main() {
+/// #     use std::{
+/// #         sync::{
+/// #             atomic::{AtomicU64, Ordering},
+/// #             Arc,
+/// #         },
+/// #         time::Duration,
+/// #     };
+/// #
+/// #     use deadline::deadline_progress;
+/// let processed = Arc::new(AtomicU64::new(0));
+///
+/// let processed_clone = processed.clone();
+/// tokio::spawn(async move {
+///     for _ in 0..10 {
+///         tokio::time::sleep(Duration::from_millis(1)).await;
+///         processed_clone.fetch_add(100, Ordering::SeqCst);
+///     }
+/// });
+///
+/// let total = deadline_progress!(
+///     Duration::from_secs(1),
+///     move || processed.load(Ordering::Relaxed),
+///     |bytes| bytes >= 1000
+/// );
+/// assert_eq!(total, 1000);
+/// # }
+/// ```
+#[macro_export]
+macro_rules! deadline_progress {
+    ($wait_limit: expr, $value: expr, $done: expr) => {{
+        let wait_limit = $crate::__wait_limit!($wait_limit);
+        let started = $crate::Instant::now();
+        match $crate::instrument(
+            $crate::deadline_progress_inner(&$crate::RuntimeSleeper, wait_limit, $value, $done),
+            stringify!($done),
+            wait_limit,
+        )
+        .await
+        {
+            Ok(value) => value,
+            Err((None, error)) => $crate::__deadline_failed!(
+                format!(
+                    "{} on {}",
+                    $crate::__condition_str!($done),
+                    $crate::__condition_str!($value)
+                ),
+                wait_limit,
+                started.elapsed(),
+                error.polls(),
+                []
+            ),
+            Err((Some(last), error)) => {
+                let elapsed = started.elapsed();
+                $crate::__deadline_failed!(
+                    format!(
+                        "{} on {}",
+                        $crate::__condition_str!($done),
+                        $crate::__condition_str!($value)
+                    ),
+                    wait_limit,
+                    elapsed,
+                    error.polls(),
+                    [],
+                    "the deadline has elapsed for condition: {} on {} (last = {}, polls = {}) after \
+                     {:?} (waited {:?})",
+                    $crate::__condition_str!($done),
+                    $crate::__condition_str!($value),
+                    last,
+                    error.polls(),
+                    wait_limit,
+                    elapsed
+                )
+            }
+        }
+    }};
+}
+
+/// Requires a closure returning a value which only ever grows, e.g. a replication offset, to reach
+/// a target before the specified duration has elapsed, and evaluates to that value.
+///
+/// The value is compared with `PartialOrd`, and must be `Debug`. Unlike a plain wait for the
+/// target, which would only time out, the macro panics as soon as the value is lower than the one
+/// before, as this points at a bug rather than slowness. The panic message then includes both
+/// values, and the evaluation which saw the regression along with how long into the wait it was.
+/// Panics raised by the closure aren't caught.
+///
+/// # Examples
+///
+/// ```rust
+/// # #[tokio::main]
+/// # async fn main() {
+/// #     use std::{
+/// #         sync::{
+/// #             atomic::{AtomicU64, Ordering},
+/// #             Arc,
+/// #         },
+/// #         time::Duration,
+/// #     };
+/// #
+/// #     use deadline::deadline_monotonic;
+/// let offset = Arc::new(AtomicU64::new(0));
+///
+/// let offset_clone = offset.clone();
+/// tokio::spawn(async move {
+///     for _ in 0..10 {
+///         tokio::time::sleep(Duration::from_millis(1)).await;
+///         offset_clone.fetch_add(10, Ordering::SeqCst);
+///     }
+/// });
+///
+/// let replicated = deadline_monotonic!(
+///     Duration::from_secs(1),
+///     move || offset.load(Ordering::Relaxed),
+///     100
+/// );
+/// assert_eq!(replicated, 100);
+/// # }
+/// ```
+#[macro_export]
+macro_rules! deadline_monotonic {
+    ($wait_limit: expr, $value: expr, $target: expr $(,)?) => {{
+        let wait_limit = $crate::__wait_limit!($wait_limit);
+        let started = $crate::Instant::now();
+        let target = $target;
+        match $crate::instrument(
+            $crate::deadline_monotonic_inner(&$crate::RuntimeSleeper, wait_limit, $value, &target),
+            stringify!($value),
+            wait_limit,
+        )
+        .await
+        {
+            Ok(value) => value,
+            Err($crate::NotMonotonic::Regressed {
+                previous,
+                value,
+                poll,
+                after,
+            }) => panic!(
+                "the value went backwards from {:?} to {:?} on evaluation {} after {:?}: {}",
+                previous,
+                value,
+                poll,
+                after,
+                $crate::__condition_str!($value)
+            ),
+            Err($crate::NotMonotonic::TimedOut { last: None, polls }) => {
+                $crate::__deadline_failed!(
+                    format!("{} to reach {:?}", $crate::__condition_str!($value), target),
+                    wait_limit,
+                    started.elapsed(),
+                    polls,
+                    []
+                )
+            }
+            Err($crate::NotMonotonic::TimedOut {
+                last: Some(last),
+                polls,
+            }) => {
+                let elapsed = started.elapsed();
+                $crate::__deadline_failed!(
+                    format!("{} to reach {:?}", $crate::__condition_str!($value), target),
+                    wait_limit,
+                    elapsed,
+                    polls,
+                    [],
+                    "the deadline has elapsed for {} to reach {:?} (last = {:?}, polls = {}) \
+                     after {:?} (waited {:?})",
+                    $crate::__condition_str!($value),
+                    target,
+                    last,
+                    polls,
+                    wait_limit,
+                    elapsed
+                )
+            }
+        }
+    }};
+}
+
+/// Requires every one of several condition closures to return `true` before the specified duration
+/// has elapsed.
+///
+/// The conditions share the wait limit and are all evaluated each poll, so they are waited on
+/// concurrently rather than one after the other. Once a condition has returned `true` it's
+/// considered met and isn't evaluated again. On timeout, the panic message lists the conditions
+/// that were never met. Up to 12 conditions are supported.
+///
+/// # Examples
+///
+/// ```rust
+/// # #[tokio::main]
+/// # async fn main() {
+/// #     use std::{
+/// #         sync::{
+/// #             atomic::{AtomicBool, Ordering},
+/// #             Arc,
+/// #         },
+/// #         time::Duration,
+/// #     };
+/// #
+/// #     use deadline::deadline_all;
+/// let server_up = Arc::new(AtomicBool::new(false));
+/// let db_migrated = Arc::new(AtomicBool::new(false));
+///
+/// let (server_up_clone, db_migrated_clone) = (server_up.clone(), db_migrated.clone());
+/// tokio::spawn(async move {
+///     tokio::time::sleep(Duration::from_millis(5)).await;
+///     server_up_clone.store(true, Ordering::SeqCst);
+///     tokio::time::sleep(Duration::from_millis(5)).await;
+///     db_migrated_clone.store(true, Ordering::SeqCst);
+/// });
+///
+/// deadline_all!(
+///     Duration::from_millis(100),
+///     [
+///         move || server_up.load(Ordering::Relaxed),
+///         move || db_migrated.load(Ordering::Relaxed),
+///     ]
+/// );
+/// # }
+/// ```
+#[macro_export]
+macro_rules! deadline_all {
+    ($wait_limit: expr, [$($condition: expr),+ $(,)?]) => {{
+        let wait_limit = $crate::__wait_limit!($wait_limit);
+        let started = $crate::Instant::now();
+        if let Err((pending, error)) = $crate::instrument(
+            $crate::deadline_all_inner(&$crate::RuntimeSleeper, wait_limit, ($($condition,)+)),
+            stringify!($($condition),+),
+            wait_limit,
+        )
+        .await
+        {
+            let conditions = [$($crate::__condition_str!($condition)),+];
+            let pending = pending.iter().map(|index| &conditions[*index]).collect::<Vec<_>>();
+            let elapsed = started.elapsed();
+            $crate::__deadline_failed!(
+                format!("{:?}", pending),
+                wait_limit,
+                elapsed,
+                error.polls(),
+                [],
+                "the deadline has elapsed with conditions still false: {:?} after {:?} (waited \
+                 {:?})",
+                pending,
+                wait_limit,
+                elapsed
+            );
+        }
+    }};
+}
+
+/// Requires any one of several condition closures to return `true` before the specified duration
+/// has elapsed, and evaluates to the index of the condition which did.
+///
+/// This is the dual of [`deadline_all!`]: the conditions are evaluated in order each poll, and the
+/// macro returns as soon as one of them is `true`. On timeout, the panic message lists all the
+/// conditions. Up to 12 conditions are supported.
+///
+/// # Examples
+///
+/// ```rust
+/// # #[tokio::main]
+/// # async fn main() {
+/// #     use std::{
+/// #         sync::{
+/// #             atomic::{AtomicBool, Ordering},
+/// #             Arc,
+/// #         },
+/// #         time::Duration,
+/// #     };
+/// #
+/// #     use deadline::deadline_any;
+/// let succeeded = Arc::new(AtomicBool::new(false));
+/// let failed = Arc::new(AtomicBool::new(false));
+///
+/// let succeeded_clone = succeeded.clone();
+/// tokio::spawn(async move {
+///     tokio::time::sleep(Duration::from_millis(5)).await;
+///     succeeded_clone.store(true, Ordering::SeqCst);
+/// });
+///
+/// let fired = deadline_any!(
+///     Duration::from_millis(100),
+///     [
+///         move || succeeded.load(Ordering::Relaxed),
+///         move || failed.load(Ordering::Relaxed),
+///     ]
+/// );
+/// assert_eq!(fired, 0);
+/// # }
+/// ```
+#[macro_export]
+macro_rules! deadline_any {
+    ($wait_limit: expr, [$($condition: expr),+ $(,)?]) => {{
+        let wait_limit = $crate::__wait_limit!($wait_limit);
+        let started = $crate::Instant::now();
+        match $crate::instrument(
+            $crate::deadline_any_inner(&$crate::RuntimeSleeper, wait_limit, ($($condition,)+)),
+            stringify!($($condition),+),
+            wait_limit,
+        )
+        .await
+        {
+            Ok(index) => index,
+            Err(error) => {
+                let conditions = [$($crate::__condition_str!($condition)),+];
+                let elapsed = started.elapsed();
+                $crate::__deadline_failed!(
+                    format!("{:?}", conditions),
+                    wait_limit,
+                    elapsed,
+                    error.polls(),
+                    [],
+                    "the deadline has elapsed with none of the conditions true: {:?} after {:?} \
+                     (waited {:?})",
+                    conditions,
+                    wait_limit,
+                    elapsed
+                )
+            }
+        }
+    }};
+}
+
+/// Requires each of several condition closures to return `true` in turn, each before its own
+/// duration has elapsed.
+///
+/// This waits on a pipeline of stages, like a server accepting connections quickly but only being
+/// fully warmed up much later: the first condition is waited on for the first wait limit, then the
+/// second for the second, and so on. Each stage's wait limit starts when the previous stage is met,
+/// unlike [`deadline_all!`]'s shared one. On timeout, the panic message names the stage that
+/// failed, counting from 1, along with its condition.
+///
+/// # Examples
+///
+/// ```rust
+/// # #[tokio::main]
+/// # async fn main() {
+/// #     use std::{
+/// #         sync::{
+/// #             atomic::{AtomicBool, Ordering},
+/// #             Arc,
+/// #         },
+/// #         time::Duration,
+/// #     };
+/// #
+/// #     use deadline::deadline_stages;
+/// let accepting = Arc::new(AtomicBool::new(false));
+/// let warmed_up = Arc::new(AtomicBool::new(false));
+///
+/// let (accepting_clone, warmed_up_clone) = (accepting.clone(), warmed_up.clone());
+/// tokio::spawn(async move {
+///     tokio::time::sleep(Duration::from_millis(5)).await;
+///     accepting_clone.store(true, Ordering::SeqCst);
+///     tokio::time::sleep(Duration::from_millis(50)).await;
+///     warmed_up_clone.store(true, Ordering::SeqCst);
+/// });
+///
+/// deadline_stages!([
+///     (Duration::from_millis(20), move || accepting.load(Ordering::Relaxed)),
+///     (Duration::from_millis(200), move || warmed_up.load(Ordering::Relaxed)),
+/// ]);
+/// # }
+/// ```
+#[macro_export]
+macro_rules! deadline_stages {
+    ([$(($wait_limit: expr, $condition: expr)),+ $(,)?]) => {{
+        let stages = [$(stringify!($condition)),+].len();
+        let mut stage = 0;
+        $(
+            stage += 1;
+            let wait_limit = $crate::__wait_limit!($wait_limit);
+            let started = $crate::Instant::now();
+            if let Err(error) = $crate::instrument(
+                $crate::deadline_future(wait_limit, $condition),
+                stringify!($condition),
+                wait_limit,
+            )
+            .await
+            {
+                let elapsed = started.elapsed();
+                $crate::__deadline_failed!(
+                    $crate::__condition_str!($condition),
+                    wait_limit,
+                    elapsed,
+                    error.polls(),
+                    [],
+                    "the deadline of stage {} of {} has elapsed for condition: {} after {:?} \
+                     (waited {:?})",
+                    stage,
+                    stages,
+                    $crate::__condition_str!($condition),
+                    wait_limit,
+                    elapsed
+                );
+            }
+        )+
+    }};
+}
+
+/// Requires a condition closure to keep returning `true` for the whole specified duration.
+///
+/// This is the opposite of [`deadline!`]: the closure is evaluated every [`DEFAULT_POLL_INTERVAL`]
+/// and the macro panics as soon as it returns `false`, reporting how long the condition held.
+/// Otherwise it returns once the duration has elapsed. With [`test_util::force_timeout`] in
+/// effect, it panics straight away as if the condition held for no time.
+///
+/// # Examples
+///
+/// ```rust
+/// # #[tokio::main]
+/// # async fn main() {
+/// #     use std::{
+/// #         sync::{
+/// #             atomic::{AtomicUsize, Ordering},
+/// #             Arc,
+/// #         },
+/// #         time::Duration,
+/// #     };
+/// #
+/// #     use deadline::deadline_sustained;
+/// let queue_len = Arc::new(AtomicUsize::new(0));
+///
+/// deadline_sustained!(Duration::from_millis(20), move || {
+///     queue_len.load(Ordering::Relaxed) < 10
+/// });
+/// # }
+/// ```
+#[macro_export]
+macro_rules! deadline_sustained {
+    ($duration: expr, $condition: expr) => {{
+        let duration = $crate::__wait_limit!($duration);
+        if let Err((held, polls)) =
+            $crate::deadline_sustained_inner(&$crate::RuntimeSleeper, duration, $condition).await
+        {
+            $crate::__deadline_failed!(
+                $crate::__condition_str!($condition),
+                duration,
+                held,
+                polls,
+                [],
+                "the condition only held for {:?} of {:?}: {}",
+                held,
+                duration,
+                $crate::__condition_str!($condition)
+            );
+        }
+    }};
+}
+
+/// Requires a condition closure to become `true`, then to stay `true` for a settle period, before
+/// the specified duration has elapsed.
+///
+/// This combines [`deadline!`] and [`deadline_sustained!`] for systems that can briefly reach a
+/// state and leave it again: the closure is evaluated every [`DEFAULT_POLL_INTERVAL`], and once it
+/// returns `true`, it must keep doing so for `settle`. If it returns `false` before then, the
+/// settle period starts over the next time it's `true`, all within the same `wait_limit`. On
+/// timeout, the panic message tells a condition that was never `true` from one that kept flapping.
+/// Like [`deadline_sustained!`]'s duration, `settle` isn't scaled by `DEADLINE_SCALE`.
+///
+/// # Examples
+///
+/// ```rust
+/// # #[tokio::main]
+/// # async fn main() {
+/// #     use std::{
+/// #         sync::{
+/// #             atomic::{AtomicBool, Ordering},
+/// #             Arc,
+/// #         },
+/// #         time::Duration,
+/// #     };
+/// #
+/// #     use deadline::deadline_settle;
+/// let healthy = Arc::new(AtomicBool::new(false));
+///
+/// let healthy_clone = healthy.clone();
+/// tokio::spawn(async move {
+///     // Briefly healthy while starting up, then healthy for good.
+///     for healthy in [true, false, true] {
+///         tokio::time::sleep(Duration::from_millis(5)).await;
+///         healthy_clone.store(healthy, Ordering::SeqCst);
+///     }
+/// });
+///
+/// deadline_settle!(Duration::from_secs(1), Duration::from_millis(20), move || {
+///     healthy.load(Ordering::Relaxed)
+/// });
+/// # }
+/// ```
+#[macro_export]
+macro_rules! deadline_settle {
+    ($wait_limit: expr, $settle: expr, $condition: expr) => {{
+        let wait_limit = $crate::__wait_limit!($wait_limit);
+        let settle = $crate::__wait_limit!($settle);
+        let started = $crate::Instant::now();
+        match $crate::instrument(
+            $crate::deadline_settle_inner(&$crate::RuntimeSleeper, wait_limit, settle, $condition),
+            stringify!($condition),
+            wait_limit,
+        )
+        .await
+        {
+            Ok(()) => {}
+            Err((None, error)) => {
+                let elapsed = started.elapsed();
+                $crate::__deadline_failed!(
+                    $crate::__condition_str!($condition),
+                    wait_limit,
+                    elapsed,
+                    error.polls(),
+                    [],
+                    "the deadline has elapsed for condition: {} after {:?} (waited {:?}), it was \
+                     never true",
+                    $crate::__condition_str!($condition),
+                    wait_limit,
+                    elapsed
+                )
+            }
+            Err((Some(flaps), error)) => {
+                let elapsed = started.elapsed();
+                $crate::__deadline_failed!(
+                    $crate::__condition_str!($condition),
+                    wait_limit,
+                    elapsed,
+                    error.polls(),
+                    [],
+                    "the deadline has elapsed for condition: {} after {:?} (waited {:?}) before it \
+                     stayed true for {:?}, it became false again {} times",
+                    $crate::__condition_str!($condition),
+                    wait_limit,
+                    elapsed,
+                    settle,
+                    flaps
+                )
+            }
+        }
+    }};
+}
+
+/// Requires a condition closure to never return `true` before the specified duration has elapsed.
+///
+/// The macro panics as soon as the closure returns `true`, reporting when that happened. This is
+/// [`deadline_sustained!`] on the negated condition, but reads the right way round when checking
+/// that a bad state doesn't occur. With [`test_util::force_timeout`] in effect, it panics straight
+/// away as if the condition became `true` at once.
+///
+/// # Examples
+///
+/// ```rust
+/// # #[tokio::main]
+/// # async fn main() {
+/// #     use std::{
+/// #         sync::{
+/// #             atomic::{AtomicBool, Ordering},
+/// #             Arc,
+/// #         },
+/// #         time::Duration,
+/// #     };
+/// #
+/// #     use deadline::deadline_never;
+/// let errored = Arc::new(AtomicBool::new(false));
+///
+/// deadline_never!(Duration::from_millis(20), move || errored.load(Ordering::Relaxed));
+/// # }
+/// ```
+#[macro_export]
+macro_rules! deadline_never {
+    ($wait_limit: expr, $condition: expr) => {{
+        let wait_limit = $crate::__wait_limit!($wait_limit);
+        if let Err((elapsed, polls)) =
+            $crate::deadline_never_inner(&$crate::RuntimeSleeper, wait_limit, $condition).await
+        {
+            $crate::__deadline_failed!(
+                $crate::__condition_str!($condition),
+                wait_limit,
+                elapsed,
+                polls,
+                [],
+                "the condition became true after {:?}: {}",
+                elapsed,
+                $crate::__condition_str!($condition)
+            );
+        }
+    }};
+}
+
+/// Requires a message to arrive on a receiver before the specified duration has elapsed, and
+/// evaluates to that message.
+///
+/// The receiver can be anything with a `recv` method returning a future of `Option<T>`, where
+/// `None` means the channel is closed, e.g. [`tokio::sync::mpsc::Receiver`]. With the `stream`
+/// feature, `deadline_recv!(wait_limit, stream: receiver)` waits for the next item of a
+/// [`Stream`] instead, where the stream ending counts as the channel closing. The receive future
+/// is awaited directly rather than polled in a loop. This panics with distinct messages if the
+/// deadline elapses or if the channel is closed before a message arrives.
+///
+/// [`tokio::sync::mpsc::Receiver`]: https://docs.rs/tokio/latest/tokio/sync/mpsc/struct.Receiver.html
+/// [`Stream`]: https://docs.rs/futures-core/latest/futures_core/stream/trait.Stream.html
+///
+/// # Examples
+///
+/// ```rust
+/// # #[tokio::main]
+/// # async fn main() {
+/// #     use std::time::Duration;
+/// #
+/// #     use deadline::deadline_recv;
+/// let (tx, mut rx) = tokio::sync::mpsc::channel(1);
+///
+/// tokio::spawn(async move {
+///     tokio::time::sleep(Duration::from_millis(5)).await;
+///     tx.send(42).await.unwrap();
+/// });
+///
+/// let x = deadline_recv!(Duration::from_millis(100), rx);
+/// assert_eq!(x, 42);
+/// # }
+/// ```
+#[macro_export]
+macro_rules! deadline_recv {
+    ($wait_limit: expr, stream: $stream: expr) => {
+        $crate::__deadline_recv!(
+            $wait_limit,
+            $stream,
+            $crate::stream_next(&mut $stream),
+            "the stream ended before an item arrived on",
+            "an item"
+        )
+    };
+    ($wait_limit: expr, $receiver: expr) => {
+        $crate::__deadline_recv!(
+            $wait_limit,
+            $receiver,
+            $receiver.recv(),
+            "the channel was closed before a message arrived on",
+            "a message"
+        )
+    };
+}
+
+/// Awaits the receive future of [`deadline_recv!`](crate::deadline_recv) under the wait limit.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __deadline_recv {
+    ($wait_limit: expr, $receiver: expr, $recv: expr, $closed: literal, $awaited: literal) => {{
+        let wait_limit = $crate::__wait_limit!($wait_limit);
+        match $crate::instrument(
+            $crate::deadline_recv_inner(wait_limit, $recv),
+            stringify!($receiver),
+            wait_limit,
+        )
+        .await
+        {
+            Ok(Some(message)) => message,
+            Ok(None) => panic!(concat!($closed, ": {}"), stringify!($receiver)),
+            Err(error) => $crate::__deadline_failed!(
+                stringify!($receiver),
+                wait_limit,
+                error.waited(),
+                error.polls(),
+                [],
+                concat!(
+                    "the deadline has elapsed waiting for ",
+                    $awaited,
+                    " on: {} after {:?} (waited {:?})"
+                ),
+                stringify!($receiver),
+                wait_limit,
+                error.waited()
+            ),
+        }
+    }};
+}
+
+/// Requires a `poll_fn`-style closure to return [`Poll::Ready`] before the specified duration has
+/// elapsed.
+///
+/// This is the lowest-level macro of the crate, for conditions that are themselves event driven:
+/// rather than being evaluated in a loop, the closure is polled with the task's
+/// [`Context`](core::task::Context) like a future, and returns [`Poll::Pending`] after registering
+/// the context's waker wherever the state it waits on will signal a change. It's then only polled
+/// again once woken, and one last time when the deadline elapses, so there's no polling interval
+/// and no wasted evaluations. As with any future, a closure returning `Pending` without arranging
+/// for the waker to be woken is never polled again.
+///
+/// # Examples
+///
+/// ```rust
+/// # #[tokio::main]
+/// # async fn main() {
+/// #     use std::{
+/// #         sync::{Arc, Mutex},
+/// #         task::{Poll, Waker},
+/// #         time::Duration,
+/// #     };
+/// #
+/// #     use deadline::deadline_poll_fn;
+/// let state = Arc::new(Mutex::new((false, None::<Waker>)));
+///
+/// let state_clone = state.clone();
+/// tokio::spawn(async move {
+///     tokio::time::sleep(Duration::from_millis(5)).await;
+///     let mut state = state_clone.lock().unwrap();
+///     state.0 = true;
+///     if let Some(waker) = state.1.take() {
+///         waker.wake();
+///     }
+/// });
+///
+/// deadline_poll_fn!(Duration::from_millis(100), move |cx| {
+///     let mut state = state.lock().unwrap();
+///     if state.0 {
+///         Poll::Ready(())
+///     } else {
+///         state.1 = Some(cx.waker().clone());
+///         Poll::Pending
+///     }
+/// });
+/// # }
+/// ```
+#[macro_export]
+macro_rules! deadline_poll_fn {
+    ($wait_limit: expr, $poll: expr) => {{
+        let wait_limit = $crate::__wait_limit!($wait_limit);
+        let started = $crate::Instant::now();
+        if let Err(error) = $crate::instrument(
+            $crate::deadline_poll_fn_inner(wait_limit, $poll),
+            stringify!($poll),
+            wait_limit,
+        )
+        .await
+        {
+            $crate::__deadline_failed!(
+                $crate::__condition_str!($poll),
+                wait_limit,
+                started.elapsed(),
+                error.polls(),
+                []
+            );
+        }
+    }};
+}
+
+/// Like [`deadline!`], but for an async condition: a closure returning a future that resolves to
+/// whether the condition holds.
+///
+/// Each evaluation calls the closure and awaits its future, then sleeps for
+/// [`DEFAULT_POLL_INTERVAL`] until the next one unless it resolved to `true`. The futures don't
+/// need to be `'static`: they can borrow whatever the closure borrows, e.g. `|| async { ... }` over
+/// local variables, since each one is dropped before the next evaluation. Awaiting an evaluation
+/// counts towards the deadline, and one still pending when it elapses is dropped.
+///
+/// # Examples
+///
+/// ```rust
+/// # #[tokio::main]
+/// # async fn main() {
+/// #     use std::{sync::Arc, time::Duration};
+/// #
+/// #     use deadline::deadline_async;
+/// #     use tokio::sync::RwLock;
+/// let status = Arc::new(RwLock::new("starting"));
+///
+/// let status_clone = status.clone();
+/// tokio::spawn(async move {
+///     tokio::time::sleep(Duration::from_millis(5)).await;
+///     *status_clone.write().await = "ready";
+/// });
+///
+/// deadline_async!(Duration::from_millis(100), || async {
+///     *status.read().await == "ready"
+/// });
+/// # }
+/// ```
+#[macro_export]
+macro_rules! deadline_async {
+    ($wait_limit: expr, $condition: expr) => {{
+        let wait_limit = $crate::__wait_limit!($wait_limit);
+        let started = $crate::Instant::now();
+        if let Err(error) = $crate::instrument(
+            $crate::deadline_async_inner(wait_limit, $condition),
+            stringify!($condition),
+            wait_limit,
+        )
+        .await
+        {
+            $crate::__deadline_failed!(
+                $crate::__condition_str!($condition),
+                wait_limit,
+                started.elapsed(),
+                error.polls(),
+                []
+            );
+        }
+    }};
+}
+
+/// Defines `#[tokio::test]` functions that fail if they take longer than the wait limit as a whole.
+///
+/// Where the other macros bound a single assertion, this bounds the entire test, e.g. one starting
+/// a system, waiting for it to be ready, running assertions and shutting it down, so that a hang
+/// anywhere fails the test rather than stalling the suite. The test panics with its path and the
+/// wait limit once it elapses, dropping the test's future wherever it's stuck. The wait limit is
+/// scaled like the macros', and the tests can return a `Result` or carry attributes of their own,
+/// like `#[should_panic]`, which go through to the test function.
+///
+/// This is a declarative macro rather than an attribute, so the crate doesn't need a separate
+/// proc-macro crate. The tests run on the runtime of `#[tokio::test]`, so the calling crate must
+/// depend on `tokio` with the `macros` and `rt` features. Requires the `tokio` backend.
+///
+/// # Examples
+///
+/// ```rust
+/// # use std::time::Duration;
+/// #
+/// # use deadline::{deadline, deadline_test};
+/// deadline_test! {
+///     Duration::from_secs(5);
+///
+///     async fn it_starts_up() {
+///         let x = 42;
+///         deadline!(Duration::from_millis(100), move || x == 42);
+///     }
+///
+///     #[ignore]
+///     async fn it_shuts_down() -> Result<(), String> {
+///         Ok(())
+///     }
+/// }
+/// ```
+#[cfg(feature = "tokio")]
+#[macro_export]
+macro_rules! deadline_test {
+    (
+        $wait_limit: expr;
+        $(
+            $(#[$attr: meta])*
+            async fn $name: ident() $(-> $ret: ty)? $body: block
+        )*
+    ) => {
+        $(
+            #[::tokio::test]
+            $(#[$attr])*
+            async fn $name() $(-> $ret)? {
+                let wait_limit = $crate::__wait_limit!($wait_limit);
+                let started = $crate::Instant::now();
+                match $crate::deadline_test_inner(wait_limit, async move $body).await {
+                    ::core::option::Option::Some(output) => output,
+                    // The test isn't evaluated like a condition, only run once.
+                    ::core::option::Option::None => $crate::__deadline_failed!(
+                        concat!(module_path!(), "::", stringify!($name)),
+                        wait_limit,
+                        started.elapsed(),
+                        1,
+                        [],
+                        "the test {} exceeded its deadline of {:?}",
+                        concat!(module_path!(), "::", stringify!($name)),
+                        wait_limit
+                    ),
+                }
+            }
+        )*
+    };
+}
+
+/// Like [`deadline!`], but re-evaluates the condition each time a
+/// [`Notify`](https://docs.rs/tokio/latest/tokio/sync/struct.Notify.html) is notified rather than
+/// polling it.
+///
+/// The condition is evaluated once upfront, then after every notification until it returns `true`
+/// or the deadline elapses. A notification only means the state may have changed, so the condition
+/// is always re-checked rather than assumed to hold. Requires the `tokio` backend.
+///
+/// Beware of lost wakeups: [`Notify::notify_one`] stores a permit when no task is waiting, so a
+/// notification sent between two evaluations still wakes the next wait, but
+/// [`Notify::notify_waiters`] only wakes tasks already waiting. With the latter, a change made
+/// while the condition is being evaluated goes unnoticed until the next notification, or the
+/// deadline.
+///
+/// [`Notify::notify_one`]: https://docs.rs/tokio/latest/tokio/sync/struct.Notify.html#method.notify_one
+/// [`Notify::notify_waiters`]: https://docs.rs/tokio/latest/tokio/sync/struct.Notify.html#method.notify_waiters
+///
+/// # Examples
+///
+/// ```rust
+/// # #[tokio::main]
+/// # async fn main() {
+/// #     use std::{
+/// #         sync::{
+/// #             atomic::{AtomicI32, Ordering},
+/// #             Arc,
+/// #         },
+/// #         time::Duration,
+/// #     };
+/// #
+/// #     use deadline::deadline_notify;
+/// #     use tokio::sync::Notify;
+/// let x = Arc::new(AtomicI32::new(41));
+/// let changed = Arc::new(Notify::new());
+///
+/// let (x_clone, changed_clone) = (x.clone(), changed.clone());
+/// tokio::spawn(async move {
+///     tokio::time::sleep(Duration::from_millis(5)).await;
+///     x_clone.fetch_add(1, Ordering::SeqCst);
+///     changed_clone.notify_one();
+/// });
+///
+/// deadline_notify!(Duration::from_millis(100), changed, move || {
+///     x.load(Ordering::Relaxed) == 42
+/// });
+/// # }
+/// ```
+#[cfg(feature = "tokio")]
+#[macro_export]
+macro_rules! deadline_notify {
+    ($wait_limit: expr, $notify: expr, $condition: expr) => {
+        $crate::deadline_strategy!(
+            $wait_limit,
+            $crate::PollStrategy::Notify(&$notify),
+            $condition
+        )
+    };
+}
+
+/// Requires the value behind a tokio
+/// [`Mutex`](https://docs.rs/tokio/latest/tokio/sync/struct.Mutex.html) or
+/// [`RwLock`](https://docs.rs/tokio/latest/tokio/sync/struct.RwLock.html) to satisfy a predicate
+/// before the specified duration has elapsed.
+///
+/// Each evaluation awaits the lock, a read lock for an `RwLock`, and passes a reference to the
+/// value to the predicate, then releases the lock before sleeping for [`DEFAULT_POLL_INTERVAL`],
+/// so other tasks can take it in between. The lock can be passed through a reference or an `Arc`
+/// as well. Waiting for the lock counts towards the deadline. Requires the `tokio` backend.
+///
+/// # Examples
+///
+/// ```rust
+/// # #[tokio::main]
+/// # async fn main() {
+/// #     use std::{sync::Arc, time::Duration};
+/// #
+/// #     use deadline::deadline_lock;
+/// #     use tokio::sync::Mutex;
+/// let state = Arc::new(Mutex::new(false));
+///
+/// let state_clone = state.clone();
+/// tokio::spawn(async move {
+///     tokio::time::sleep(Duration::from_millis(5)).await;
+///     *state_clone.lock().await = true;
+/// });
+///
+/// deadline_lock!(Duration::from_millis(100), state, |ready| *ready);
+/// # }
+/// ```
+#[cfg(feature = "tokio")]
+#[macro_export]
+macro_rules! deadline_lock {
+    ($wait_limit: expr, $lock: expr, $predicate: expr) => {{
+        #[allow(unused_imports)]
+        use $crate::AsyncLock as _;
+
+        let wait_limit = $crate::__wait_limit!($wait_limit);
+        let started = $crate::Instant::now();
+        let lock = &$lock;
+        let predicate = lock.predicate($predicate);
+        if let ::core::result::Result::Err(error) = $crate::instrument(
+            $crate::deadline_async_inner(wait_limit, || lock.with_guard(&predicate)),
+            stringify!($predicate),
+            wait_limit,
+        )
+        .await
+        {
+            let elapsed = started.elapsed();
+            $crate::__deadline_failed!(
+                format!(
+                    "{} on: {}",
+                    $crate::__condition_str!($predicate),
+                    stringify!($lock)
+                ),
+                wait_limit,
+                elapsed,
+                error.polls(),
+                [],
+                "the deadline has elapsed for condition: {} on: {} after {:?} (waited {:?})",
+                $crate::__condition_str!($predicate),
+                stringify!($lock),
+                wait_limit,
+                elapsed
+            );
+        }
+    }};
+}
+
+/// Requires the value of a tokio
+/// [`watch::Receiver`](https://docs.rs/tokio/latest/tokio/sync/watch/struct.Receiver.html) to
+/// satisfy a predicate before the specified duration has elapsed.
+///
+/// Rather than polling, the predicate is evaluated on the current value upfront, so a value that
+/// already satisfies it returns straight away, then again each time the value changes. The
+/// receiver is taken by mutable reference and marks each value it evaluates as seen. This panics
+/// if the deadline elapses, or if the sender is dropped before the predicate holds, as the value
+/// can't change anymore. Requires the `tokio` backend.
+///
+/// # Examples
+///
+/// ```rust
+/// # #[tokio::main]
+/// # async fn main() {
+/// #     use std::time::Duration;
+/// #
+/// #     use deadline::deadline_watch;
+/// #     use tokio::sync::watch;
+/// let (tx, mut rx) = watch::channel(0);
+///
+/// tokio::spawn(async move {
+///     for i in 1..=3 {
+///         tokio::time::sleep(Duration::from_millis(2)).await;
+///         tx.send(i).unwrap();
+///     }
+/// });
+///
+/// deadline_watch!(Duration::from_millis(100), rx, |value| *value == 3);
+/// # }
+/// ```
+#[cfg(feature = "tokio")]
+#[macro_export]
+macro_rules! deadline_watch {
+    ($wait_limit: expr, $rx: expr, $predicate: expr) => {{
+        let wait_limit = $crate::__wait_limit!($wait_limit);
+        let started = $crate::Instant::now();
+        match $crate::instrument(
+            $crate::deadline_watch_inner(wait_limit, &mut $rx, $predicate),
+            stringify!($predicate),
+            wait_limit,
+        )
+        .await
+        {
+            Ok(true) => {}
+            Ok(false) => panic!(
+                "the sender was dropped before condition: {} held on: {}",
+                $crate::__condition_str!($predicate),
+                stringify!($rx)
+            ),
+            Err(error) => {
+                let elapsed = started.elapsed();
+                $crate::__deadline_failed!(
+                    format!(
+                        "{} on: {}",
+                        $crate::__condition_str!($predicate),
+                        stringify!($rx)
+                    ),
+                    wait_limit,
+                    elapsed,
+                    error.polls(),
+                    [],
+                    "the deadline has elapsed for condition: {} on: {} after {:?} (waited {:?})",
+                    $crate::__condition_str!($predicate),
+                    stringify!($rx),
+                    wait_limit,
+                    elapsed
+                )
+            }
+        }
+    }};
+}
+
+/// Like [`deadline!`], but gives up as soon as a
+/// [`CancellationToken`](https://docs.rs/tokio-util/latest/tokio_util/sync/struct.CancellationToken.html)
+/// is cancelled.
+///
+/// This panics with a distinct message if the token is cancelled before the condition is `true`,
+/// e.g. because the task the condition depends on has died, rather than waiting out the deadline.
+/// Requires the `tokio-util` feature.
+///
+/// # Examples
+///
+/// ```rust
+/// # #[tokio::main]
+/// # async fn main() {
+/// #     use std::{
+/// #         sync::{
+/// #             atomic::{AtomicBool, Ordering},
+/// #             Arc,
+/// #         },
+/// #         time::Duration,
+/// #     };
+/// #
+/// #     use deadline::deadline_cancellable;
+/// #     use tokio_util::sync::CancellationToken;
+/// let ready = Arc::new(AtomicBool::new(false));
+/// let server_died = CancellationToken::new();
+///
+/// let ready_clone = ready.clone();
+/// let guard = server_died.clone().drop_guard();
+/// tokio::spawn(async move {
+///     let _guard = guard;
+///     tokio::time::sleep(Duration::from_millis(5)).await;
+///     ready_clone.store(true, Ordering::SeqCst);
+///     // Keep serving.
+///     std::future::pending::<()>().await;
+/// });
+///
+/// deadline_cancellable!(Duration::from_millis(100), server_died, move || {
+///     ready.load(Ordering::Relaxed)
+/// });
+/// # }
+/// ```
+#[cfg(feature = "tokio-util")]
+#[macro_export]
+macro_rules! deadline_cancellable {
+    ($wait_limit: expr, $token: expr, $condition: expr) => {{
+        let wait_limit = $crate::__wait_limit!($wait_limit);
+        let started = $crate::Instant::now();
+        match $crate::instrument(
+            $crate::deadline_cancellable_inner(
+                &$crate::RuntimeSleeper,
+                wait_limit,
+                &$token,
+                $condition,
+            ),
+            stringify!($condition),
+            wait_limit,
+        )
+        .await
+        {
+            Some(Ok(())) => {}
+            Some(Err(error)) => $crate::__deadline_failed!(
+                $crate::__condition_str!($condition),
+                wait_limit,
+                started.elapsed(),
+                error.polls(),
+                []
+            ),
+            None => panic!(
+                "the deadline was cancelled for condition: {} after {:?}",
+                $crate::__condition_str!($condition),
+                started.elapsed()
+            ),
+        }
+    }};
+}
+
+/// Requires a condition closure to return `true` within at most the specified number of
+/// evaluations.
+///
+/// This doesn't depend on the clock at all: the task yields to the runtime between evaluations and
+/// the macro panics, reporting the number of polls, if the closure still returns `false` after
+/// `max_polls` of them. It's useful on a frozen or mocked clock, or when timers are unreliable.
+///
+/// # Examples
+///
+/// ```rust
+/// # #[tokio::main]
+/// # async fn main() {
+/// #     use std::sync::atomic::{AtomicUsize, Ordering};
+/// #
+/// #     use deadline::deadline_polls;
+/// let polls = AtomicUsize::new(0);
+///
+/// deadline_polls!(10, || polls.fetch_add(1, Ordering::SeqCst) == 3);
+/// # }
+/// ```
+#[macro_export]
+macro_rules! deadline_polls {
+    ($max_polls: expr, $condition: expr) => {{
+        if let Err(error) = $crate::Deadline::new(::core::time::Duration::MAX)
+            .poll_interval(::core::time::Duration::ZERO)
+            .max_polls($max_polls)
+            .wait($condition)
+            .await
+        {
+            $crate::__deadline_failed!(
+                $crate::__condition_str!($condition),
+                ::core::time::Duration::MAX,
+                error.waited(),
+                error.polls(),
+                [],
+                "the condition was still false after {} polls: {}",
+                error.polls(),
+                $crate::__condition_str!($condition)
+            );
+        }
+    }};
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __wait_limit {
+    ($wait_limit: expr) => {{
+        #[allow(unused_imports)]
+        use $crate::{IntoWaitLimit as _, RefIntoWaitLimit as _};
+        ($wait_limit).into_wait_limit()
+    }};
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __condition_str {
+    ($condition: expr) => {
+        $crate::normalize_condition(stringify!($condition))
+    };
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use core::{
+        cell::{Cell, RefCell},
+        sync::atomic::{AtomicBool, AtomicI32, AtomicUsize, Ordering},
+    };
+    use std::sync::{Arc, Mutex};
+
+    use super::*;
+
+    #[test]
+    fn it_normalizes_closures() {
+        assert_eq!(normalize_condition("move || x == y"), "x == y");
+        assert_eq!(normalize_condition("|| x == y"), "x == y");
+        assert_eq!(normalize_condition("|x| x == y"), "|x| x == y");
+        assert_eq!(normalize_condition("x.is_ready()"), "x.is_ready()");
+    }
+
+    #[test]
+    fn it_normalizes_multi_line_conditions() {
+        assert_eq!(
+            normalize_condition(stringify!(move || {
+                let x = x.load(Ordering::Relaxed);
+                x == y
+            })),
+            "{ let x = x.load(Ordering::Relaxed); x == y }"
+        );
+        assert_eq!(normalize_condition("a\n    && b"), "a && b");
+    }
+
+    #[tokio::test]
+    #[should_panic(expected = "the deadline has elapsed for condition: x == y")]
+    async fn it_times_out() {
+        let x = 1;
+        let y = 2;
+
+        deadline!(Duration::from_millis(1), move || x == y);
+    }
+
+    #[tokio::test]
+    #[should_panic(expected = "the deadline has elapsed for condition: x == y after 1ms (waited ")]
+    async fn it_reports_the_wait_limit_on_timeout() {
+        let x = 1;
+        let y = 2;
+
+        deadline!(Duration::from_millis(1), move || x == y);
+    }
+
+    #[tokio::test]
+    async fn it_waits_until_true() {
+        let x = Arc::new(AtomicI32::new(41));
+        let y = 42;
+
+        let x_clone = x.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+            x_clone.fetch_add(1, Ordering::SeqCst);
+        });
+
+        deadline!(Duration::from_millis(10), move || {
+            x.load(Ordering::Relaxed) == y
+        });
+    }
+
+    #[tokio::test]
+    async fn it_only_waits_as_long_as_needed() {
+        let x = Arc::new(AtomicI32::new(41));
+        let y = 42;
+
+        let x_clone = x.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+            x_clone.fetch_add(1, Ordering::SeqCst);
+        });
+
+        let now = std::time::Instant::now();
+
+        deadline!(Duration::from_millis(1000), move || {
+            x.load(Ordering::Relaxed) == y
+        });
+
+        // Leave a bit of a gap, to avoid a flaky test.
+        assert!(now.elapsed() < Duration::from_millis(10));
+    }
+
+    #[tokio::test]
+    #[should_panic(expected = "the deadline has elapsed for condition: x == y after 5ms")]
+    async fn it_waits_up_to_the_default_wait_limit() {
+        set_default_wait_limit(Duration::from_millis(5));
+        let x = 1;
+        let y = 2;
+
+        deadline_default!(move || x == y);
+    }
+
+    #[tokio::test]
+    async fn it_takes_the_wait_limit_in_millis_or_secs() {
+        let x = 42;
+        let y = 42;
+        let millis: u32 = 10;
+
+        deadline_ms!(10, move || x == y);
+        deadline_ms!(millis * 2, move || x == y);
+        deadline_secs!(1, move || x == y);
+    }
+
+    #[tokio::test]
+    #[should_panic(expected = "the deadline has elapsed for condition: x == y after 1ms")]
+    async fn it_times_out_in_millis() {
+        let x = 1;
+        let y = 2;
+
+        deadline_ms!(1, move || x == y);
+    }
+
+    #[tokio::test]
+    async fn it_waits_until_an_instant() {
+        let x = Arc::new(AtomicI32::new(41));
+        let y = 42;
+
+        let x_clone = x.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+            x_clone.fetch_add(1, Ordering::SeqCst);
+        });
+
+        let budget = std::time::Instant::now() + Duration::from_millis(100);
+        deadline_until!(budget, move || x.load(Ordering::Relaxed) == y);
+    }
+
+    #[tokio::test]
+    async fn it_evaluates_once_for_an_instant_in_the_past() {
+        let calls = AtomicI32::new(0);
+        let past = std::time::Instant::now() - Duration::from_millis(1);
+
+        deadline_until!(past, || calls.fetch_add(1, Ordering::SeqCst) == 0);
+
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    #[should_panic(expected = "the deadline has elapsed for condition: x == y")]
+    async fn it_times_out_for_an_instant_in_the_past() {
+        let x = 1;
+        let y = 2;
+
+        deadline_until!(std::time::Instant::now(), move || x == y);
+    }
+
+    #[cfg(feature = "tokio")]
+    #[tokio::test(start_paused = true)]
+    #[should_panic(expected = "the deadline has elapsed for condition: x == y after 0ns")]
+    async fn it_reads_the_paused_clock_until_an_instant() {
+        let (x, y) = (1, 2);
+        let budget = Instant::now() + Duration::from_secs(1);
+
+        // The instant has passed on the runtime's clock, though not on the system's.
+        tokio::time::advance(Duration::from_secs(2)).await;
+        deadline_until!(budget, move || x == y);
+    }
+
+    #[tokio::test]
+    async fn it_polls_with_the_default_poll_strategy() {
+        // Equivalent to the built-in default, so the tests running meanwhile are unaffected.
+        let strategy = PollStrategy::Backoff {
+            start: DEFAULT_POLL_INTERVAL,
+            cap: DEFAULT_POLL_INTERVAL,
+        };
+        set_defaults(Defaults::new().poll_strategy(strategy));
+        assert!(matches!(
+            default_poll_strategy(),
+            PollStrategy::Backoff { start, cap }
+                if start == DEFAULT_POLL_INTERVAL && cap == DEFAULT_POLL_INTERVAL
+        ));
+
+        let x = 42;
+        deadline!(Duration::from_millis(10), move || x == 42);
+
+        let sleeper = MockSleeper::new();
+        let result = deadline_inner_catch_unwind(
+            &sleeper,
+            Duration::from_millis(10),
+            PollStrategy::Backoff {
+                start: Duration::from_millis(1),
+                cap: Duration::from_millis(4),
+            },
+            false,
+            || false,
+        )
+        .await;
+
+        assert_eq!(result, Err((None, 5)));
+        assert_eq!(sleeper.sleeps(), millis(&[1, 2, 4, 3]));
+    }
+
+    #[tokio::test]
+    async fn it_catches_panics_in_the_condition() {
+        let sleeper = MockSleeper::new();
+        let calls = Cell::new(0);
+
+        let result = deadline_inner_catch_unwind(
+            &sleeper,
+            Duration::from_millis(10),
+            PollStrategy::Interval(DEFAULT_POLL_INTERVAL),
+            false,
+            || {
+                calls.set(calls.get() + 1);
+                assert!(calls.get() < 3, "not ready yet");
+                false
+            },
+        )
+        .await;
+
+        assert_eq!(
+            result,
+            Err((
+                Some(ConditionPanic {
+                    after: Duration::from_millis(2),
+                    message: "not ready yet".to_owned(),
+                }),
+                3
+            ))
+        );
+    }
+
+    #[test]
+    fn it_formats_failures_by_default() {
+        let failure = DeadlineFailure::new(
+            "x == y",
+            Some("with a message"),
+            Duration::from_millis(5),
+            Duration::from_micros(5200),
+            6,
+        );
+
+        assert_eq!(
+            failure.to_string(),
+            "with a message: the deadline has elapsed for condition: x == y after 5ms (waited \
+             5.2ms)"
+        );
+    }
+
+    #[tokio::test]
+    #[should_panic(expected = r#"{"condition": "x == y", "polls": "#)]
+    async fn it_formats_failures_with_the_registered_formatter() {
+        // The formatter is global, so the other tests' failures keep the default message.
+        set_failure_formatter(|failure| match failure.message.as_deref() {
+            Some("as json") => format!(
+                r#"{{"condition": {:?}, "polls": {}}}"#,
+                failure.condition, failure.polls
+            ),
+            _ => failure.to_string(),
+        });
+        let x = 1;
+        let y = 2;
+
+        deadline!(Duration::from_millis(1), move || x == y, "as json");
+    }
+
+    #[cfg(feature = "tokio")]
+    #[tokio::test(start_paused = true)]
+    async fn it_draws_deadlines_from_a_shared_budget() {
+        let budget = Budget::new(Duration::from_millis(100));
+        let ready = Arc::new(AtomicBool::new(false));
+
+        let ready_clone = ready.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(60)).await;
+            ready_clone.store(true, Ordering::SeqCst);
+        });
+        deadline_budget!(budget, || ready.load(Ordering::SeqCst));
+
+        assert!(budget.remaining() <= Duration::from_millis(40));
+        assert!(!budget.is_exhausted());
+        deadline_budget!(budget, || true);
+    }
+
+    #[tokio::test]
+    #[should_panic(expected = "the budget was exhausted before waiting for condition: true")]
+    async fn it_fails_straight_away_once_the_budget_is_exhausted() {
+        let budget = Budget::new(Duration::ZERO);
+
+        deadline_budget!(budget, || true);
+    }
+
+    #[tokio::test]
+    async fn it_warns_instead_of_panicking_in_warn_only_mode() {
+        let x = 1;
+        let y = 2;
+
+        deadline_warn!(Duration::from_millis(1), move || x == y, "x is flaky");
+    }
+
+    #[tokio::test]
+    async fn it_returns_a_timed_out_outcome_in_warn_only_mode() {
+        let sleeper = MockSleeper::new();
+
+        let result = Deadline::new(Duration::from_millis(3))
+            .sleeper(&sleeper)
+            .warn_only()
+            .wait(|| false)
+            .await;
+
+        assert_eq!(
+            result.unwrap(),
+            DeadlineOutcome {
+                polls: 4,
+                elapsed: Duration::from_millis(3),
+                timed_out: true,
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn it_still_returns_terminal_errors_in_warn_only_mode() {
+        let result = Deadline::new(Duration::from_secs(10))
+            .warn_only()
+            .try_wait(|| Err::<bool, _>("exited"))
+            .await;
+
+        assert!(result.unwrap_err().is_terminal());
+    }
+
+    #[test]
+    fn it_panics_with_the_failure_as_the_payload() {
+        let message = String::from("with a message");
+        let failure = DeadlineFailure::new(
+            "x == y",
+            Some(&message),
+            Duration::from_millis(5),
+            Duration::from_micros(5200),
+            6,
+        );
+
+        // Calling `panic_with_failures` here would change the payload of the other tests' panics.
+        let payload = std::panic::catch_unwind(|| failure.panic_with_payload()).unwrap_err();
+        let failure = payload.downcast_ref::<DeadlineFailure<'static>>().unwrap();
+
+        assert_eq!(failure.condition, "x == y");
+        assert_eq!(failure.message.as_deref(), Some("with a message"));
+        assert_eq!(failure.elapsed, Duration::from_micros(5200));
+        assert_eq!(failure.polls, 6);
+    }
+
+    #[tokio::test]
+    #[should_panic(expected = "the deadline condition panicked after")]
+    async fn it_raises_panics_in_the_condition() {
+        let x: Option<i32> = std::hint::black_box(None);
+        let y = 42;
+
+        deadline!(Duration::from_millis(10), move || x.unwrap() == y);
+    }
+
+    #[tokio::test]
+    async fn it_retries_panics_in_the_condition() {
+        let x = Arc::new(AtomicI32::new(0));
+
+        let x_clone = x.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+            x_clone.store(42, Ordering::SeqCst);
+        });
+
+        deadline_retry_panics!(Duration::from_millis(100), move || {
+            let x = x.load(Ordering::Relaxed);
+            assert_ne!(x, 0, "not ready yet");
+            x == 42
+        });
+    }
+
+    #[tokio::test]
+    async fn it_reports_the_last_retried_panic() {
+        let sleeper = MockSleeper::new();
+
+        let result = deadline_inner_catch_unwind(
+            &sleeper,
+            Duration::from_millis(2),
+            PollStrategy::Interval(DEFAULT_POLL_INTERVAL),
+            true,
+            || -> bool { panic!("not ready yet") },
+        )
+        .await;
+
+        assert_eq!(
+            result,
+            Err((
+                Some(ConditionPanic {
+                    after: Duration::from_millis(2),
+                    message: "not ready yet".to_owned(),
+                }),
+                3
+            ))
+        );
+    }
+
+    #[tokio::test]
+    async fn it_borrows_local_state() {
+        let x = AtomicI32::new(41);
+        let y = 42;
+
+        tokio::join!(
+            async {
+                tokio::time::sleep(Duration::from_millis(5)).await;
+                x.fetch_add(1, Ordering::SeqCst);
+            },
+            async {
+                deadline!(Duration::from_millis(100), || x.load(Ordering::Relaxed)
+                    == y);
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn it_returns_an_error_on_timeout() {
+        let x = 1;
+        let y = 2;
+
+        assert!(try_deadline!(Duration::from_millis(1), move || x == y).is_err());
+    }
+
+    #[tokio::test]
+    async fn it_describes_the_condition_in_the_error() {
+        let x = 1;
+        let y = 2;
+
+        let error = try_deadline!(Duration::from_millis(10), move || x == y).unwrap_err();
+
+        assert_eq!(error.condition(), Some("x == y"));
+        assert_eq!(error.wait_limit(), Duration::from_millis(10));
+        assert!(error
+            .to_string()
+            .starts_with("deadline of 10ms has elapsed for condition: x == y after "));
+        assert!(format!("{error:?}").contains(r#"condition: Some("x == y")"#));
+    }
+
+    #[tokio::test]
+    async fn it_returns_early_on_timeout() {
+        async fn wait(reached: &Cell<bool>) -> Result<(), Box<dyn std::error::Error>> {
+            let x = 1;
+            let y = 2;
+
+            deadline_q!(Duration::from_millis(1), move || x == y);
+            reached.set(true);
+            Ok(())
+        }
+
+        let reached = Cell::new(false);
+        let error = wait(&reached).await.unwrap_err();
+
+        assert!(error.downcast_ref::<DeadlineError>().is_some());
+        assert!(!reached.get());
+    }
+
+    #[tokio::test]
+    async fn it_reports_the_wait_and_polls_in_the_error() {
+        let sleeper = MockSleeper::new();
+
+        let result = deadline_inner_with_interval(
+            &sleeper,
+            Duration::from_millis(3),
+            DEFAULT_POLL_INTERVAL,
+            || false,
+        )
+        .await;
+
+        assert!(matches!(
+            result,
+            Err(DeadlineError::TimedOut {
+                waited,
+                polls: 4,
+                ..
+            }) if waited == Duration::from_millis(3)
+        ));
+    }
+
+    #[tokio::test]
+    async fn it_returns_ok_when_true() {
+        let x = Arc::new(AtomicI32::new(41));
+        let y = 42;
+
+        let x_clone = x.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+            x_clone.fetch_add(1, Ordering::SeqCst);
+        });
+
+        assert!(try_deadline!(Duration::from_millis(100), move || {
+            x.load(Ordering::Relaxed) == y
+        })
+        .is_ok());
+    }
+
+    #[tokio::test]
+    async fn it_composes_the_future_with_others() {
+        let flag = Arc::new(AtomicBool::new(false));
+
+        let flag_clone = flag.clone();
+        let (result, ()) = tokio::join!(
+            deadline_future(Duration::from_millis(100), move || flag_clone
+                .load(Ordering::Relaxed)),
+            async {
+                tokio::time::sleep(Duration::from_millis(5)).await;
+                flag.store(true, Ordering::Relaxed);
+            },
+        );
+
+        assert!(result.is_ok());
+    }
+
+    #[tokio::test]
+    async fn it_returns_the_value() {
+        let x = Arc::new(AtomicI32::new(41));
+
+        let x_clone = x.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+            x_clone.fetch_add(1, Ordering::SeqCst);
+        });
+
+        let value = deadline_value!(Duration::from_millis(100), move || {
+            let x = x.load(Ordering::Relaxed);
+            (x == 42).then_some(x)
+        });
+
+        assert_eq!(value, 42);
+    }
+
+    #[tokio::test]
+    async fn it_waits_until_ready() {
+        let calls = Cell::new(0);
+        let condition = || {
+            calls.set(calls.get() + 1);
+            if calls.get() < 3 {
+                Poll::Pending
+            } else {
+                Poll::Ready(Ok::<_, ()>(calls.get()))
+            }
+        };
+
+        assert_eq!(deadline_try!(Duration::from_millis(100), condition), 3);
+    }
+
+    #[tokio::test]
+    #[should_panic(expected = "the deadline condition failed after ")]
+    // Would wait out the 10s before panicking with a different message without failing fast.
+    async fn it_fails_fast_on_an_error() {
+        deadline_try!(Duration::from_secs(10), || Poll::Ready(Err::<(), _>(
+            "the task crashed"
+        )));
+    }
+
+    #[tokio::test]
+    async fn it_runs_the_follow_up_once_true() {
+        let x = Cell::new(0);
+        let condition = || {
+            x.set(x.get() + 1);
+            x.get() == 3
+        };
+
+        let value = deadline_then!(Duration::from_millis(100), condition, |()| x.get());
+
+        assert_eq!(value, 3);
+    }
+
+    #[tokio::test]
+    #[should_panic(expected = "the deadline has elapsed for condition: x == y")]
+    async fn it_skips_the_follow_up_on_timeout() {
+        let x = 1;
+        let y = 2;
+
+        deadline_then!(Duration::from_millis(1), move || x == y, |()| {
+            panic!("the follow-up ran")
+        });
+    }
+
+    #[tokio::test]
+    #[should_panic(expected = "the deadline has elapsed for condition: x.checked_sub(y)")]
+    async fn it_times_out_without_a_value() {
+        let x: u32 = 1;
+        let y: u32 = 2;
+
+        let _ = deadline_value!(Duration::from_millis(1), move || x.checked_sub(y));
+    }
+
+    #[tokio::test]
+    async fn it_waits_until_the_ordering_holds() {
+        let x = Arc::new(AtomicI32::new(0));
+
+        let x_clone = x.clone();
+        tokio::spawn(async move {
+            for _ in 0..10 {
+                tokio::time::sleep(Duration::from_millis(1)).await;
+                x_clone.fetch_add(1, Ordering::SeqCst);
+            }
+        });
+
+        deadline_ge!(Duration::from_millis(100), || x.load(Ordering::Relaxed), 3);
+        deadline_gt!(Duration::from_millis(100), || x.load(Ordering::Relaxed), 5);
+        deadline_le!(Duration::from_millis(100), || x.load(Ordering::Relaxed), 10);
+        deadline_lt!(Duration::from_millis(100), || 1.5, 2.0);
+    }
+
+    #[tokio::test]
+    #[should_panic(
+        expected = "the deadline has elapsed for condition: x.load(Ordering::Relaxed) >= 42 (left = 41, right = 42)"
+    )]
+    async fn it_reports_the_last_value_short_of_the_target() {
+        let x = AtomicI32::new(41);
+
+        deadline_ge!(
+            Duration::from_millis(1),
+            move || x.load(Ordering::Relaxed),
+            42
+        );
+    }
+
+    #[tokio::test]
+    async fn it_waits_until_the_len_is_reached() {
+        let items = Arc::new(Mutex::new(Vec::new()));
+
+        let items_clone = items.clone();
+        tokio::spawn(async move {
+            for item in 0..4 {
+                tokio::time::sleep(Duration::from_millis(1)).await;
+                items_clone.lock().unwrap().push(item);
+            }
+        });
+
+        deadline_len!(Duration::from_millis(100), || items.lock().unwrap().len(), >= 2);
+        deadline_len!(
+            Duration::from_millis(100),
+            || items.lock().unwrap().len(),
+            4
+        );
+        deadline_len!(Duration::from_millis(100), || items.lock().unwrap().len(), < 5);
+    }
+
+    #[tokio::test]
+    #[should_panic(
+        expected = "the deadline has elapsed for condition: items.len() >= 3 (len = 2, expected >= 3)"
+    )]
+    async fn it_reports_the_last_len_on_timeout() {
+        let items = [1, 2];
+
+        deadline_len!(Duration::from_millis(1), move || items.len(), >= 3);
+    }
+
+    #[tokio::test]
+    async fn it_waits_until_equal() {
+        let x = Arc::new(AtomicI32::new(41));
+
+        let x_clone = x.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+            x_clone.fetch_add(1, Ordering::SeqCst);
+        });
+
+        deadline_eq!(
+            Duration::from_millis(100),
+            move || x.load(Ordering::Relaxed),
+            42
+        );
+    }
+
+    // With the `diff` feature the values are diffed instead, as tested in `mismatch`.
+    #[cfg(not(feature = "diff"))]
+    #[tokio::test]
+    #[should_panic(
+        expected = "the deadline has elapsed for condition: x.load(Ordering::Relaxed) == 42 (left = 41, right = 42)"
+    )]
+    async fn it_reports_the_last_value_on_timeout() {
+        let x = AtomicI32::new(41);
+
+        deadline_eq!(
+            Duration::from_millis(1),
+            move || x.load(Ordering::Relaxed),
+            42
+        );
+    }
+
+    #[tokio::test]
+    async fn it_starts_each_stage_when_the_previous_one_is_met() {
+        let started = Instant::now();
+        let accepting = || started.elapsed() >= Duration::from_millis(20);
+        let warmed_up = || started.elapsed() >= Duration::from_millis(25);
+
+        // The second stage would time out if its wait limit started along with the first's.
+        deadline_stages!([
+            (Duration::from_millis(100), accepting),
+            (Duration::from_millis(15), warmed_up),
+        ]);
+    }
+
+    #[tokio::test]
+    #[should_panic(
+        expected = "the deadline of stage 2 of 3 has elapsed for condition: x == 2 after 1ms"
+    )]
+    async fn it_reports_the_stage_that_timed_out() {
+        let x = 1;
+
+        deadline_stages!([
+            (Duration::from_millis(1), move || x == 1),
+            (Duration::from_millis(1), move || x == 2),
+            (Duration::from_millis(1), move || x == 3),
+        ]);
+    }
+
+    #[tokio::test]
+    async fn it_accepts_readiness_types_as_conditions() {
+        #[derive(Clone, Copy)]
+        enum Ready {
+            No,
+            Yes,
+        }
+
+        impl IsReady for Ready {
+            fn is_ready(&self) -> bool {
+                matches!(self, Ready::Yes)
+            }
+        }
+
+        let polls = Cell::new(0);
+        let evaluate = || {
+            polls.set(polls.get() + 1);
+            polls.get()
+        };
+
+        deadline!(Duration::from_millis(100), || (evaluate() > 1)
+            .then_some(()));
+        deadline!(Duration::from_millis(100), || if evaluate() > 3 {
+            Ready::Yes
+        } else {
+            Ready::No
+        });
+        let outcome = Deadline::new(Duration::from_millis(100))
+            .wait(|| u8::try_from(1500 / evaluate()))
+            .await
+            .unwrap();
+
+        assert_eq!(outcome.polls, 2);
+    }
+
+    #[tokio::test]
+    #[should_panic(expected = "the deadline has elapsed for condition: None::<()>")]
+    async fn it_times_out_on_a_readiness_type_never_ready() {
+        deadline!(Duration::from_millis(1), || None::<()>);
+    }
+
+    #[tokio::test]
+    async fn it_waits_until_both_sides_are_equal() {
+        let leader = Arc::new(AtomicI32::new(10));
+        let follower = Arc::new(AtomicI32::new(0));
+
+        let (leader_clone, follower_clone) = (leader.clone(), follower.clone());
+        tokio::spawn(async move {
+            for _ in 0..5 {
+                tokio::time::sleep(Duration::from_millis(1)).await;
+                leader_clone.fetch_add(1, Ordering::SeqCst);
+                follower_clone.fetch_add(3, Ordering::SeqCst);
+            }
+        });
+
+        deadline_eq_fn!(
+            Duration::from_millis(100),
+            move || follower.load(Ordering::Relaxed),
+            move || leader.load(Ordering::Relaxed)
+        );
+    }
+
+    #[cfg(not(feature = "diff"))]
+    #[tokio::test]
+    #[should_panic(
+        expected = "the deadline has elapsed for condition: x.load(Ordering::Relaxed) == y.load(Ordering::Relaxed) (left = 1, right = 2)"
+    )]
+    async fn it_reports_the_last_values_of_both_sides_on_timeout() {
+        let (x, y) = (AtomicI32::new(1), AtomicI32::new(2));
+
+        deadline_eq_fn!(
+            Duration::from_millis(1),
+            || x.load(Ordering::Relaxed),
+            || y.load(Ordering::Relaxed)
+        );
+    }
+
+    #[tokio::test]
+    async fn it_waits_until_the_atomic_holds_the_value() {
+        let x = Arc::new(AtomicUsize::new(41));
+
+        let x_clone = x.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+            x_clone.fetch_add(1, Ordering::SeqCst);
+        });
+
+        deadline_atomic!(Duration::from_millis(100), &x, 42, Ordering::Acquire);
+    }
+
+    #[tokio::test]
+    #[should_panic(
+        expected = "the deadline has elapsed for condition: &ready == true (left = false, right = true)"
+    )]
+    async fn it_reports_the_last_loaded_value_on_timeout() {
+        let ready = AtomicBool::new(false);
+
+        deadline_atomic!(Duration::from_millis(1), &ready, true);
+    }
+
+    #[tokio::test]
+    #[should_panic(expected = "the deadline has elapsed for condition: x == y")]
+    async fn it_times_out_with_backoff() {
+        let x = 1;
+        let y = 2;
+
+        deadline_backoff!(Duration::from_millis(10), move || x == y);
+    }
+
+    #[tokio::test]
+    async fn it_backs_off_between_evaluations() {
+        let calls = Arc::new(AtomicI32::new(0));
+
+        let calls_clone = calls.clone();
+        let result = deadline_inner_with_backoff(
+            &RuntimeSleeper,
+            Duration::from_millis(60),
+            Duration::from_millis(1),
+            Duration::from_millis(100),
+            move || {
+                calls_clone.fetch_add(1, Ordering::SeqCst);
+                false
+            },
+        )
+        .await;
+
+        // Evaluations at roughly 0, 1, 3, 7, 15, 31 and 60ms, a fixed 1ms interval would be ~60.
+        assert!(result.is_err());
+        assert!(calls.load(Ordering::SeqCst) <= 10);
+    }
+
+    #[tokio::test]
+    async fn it_waits_until_true_with_backoff() {
+        let x = Arc::new(AtomicI32::new(41));
+        let y = 42;
+
+        let x_clone = x.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+            x_clone.fetch_add(1, Ordering::SeqCst);
+        });
+
+        deadline_backoff!(
+            Duration::from_millis(100),
+            move || x.load(Ordering::Relaxed) == y,
+            Duration::from_millis(1),
+            Duration::from_millis(4)
+        );
+    }
+
+    #[tokio::test]
+    async fn it_holds_for_the_whole_duration() {
+        let calls = AtomicI32::new(0);
+
+        let now = std::time::Instant::now();
+        deadline_sustained!(Duration::from_millis(20), || {
+            calls.fetch_add(1, Ordering::SeqCst);
+            true
+        });
+
+        assert!(now.elapsed() >= Duration::from_millis(20));
+        assert!(calls.load(Ordering::SeqCst) > 1);
+    }
+
+    #[tokio::test]
+    #[should_panic(expected = "the condition only held for")]
+    async fn it_fails_when_the_condition_stops_holding() {
+        let x = Arc::new(AtomicI32::new(0));
+
+        let x_clone = x.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+            x_clone.store(10, Ordering::SeqCst);
+        });
+
+        deadline_sustained!(Duration::from_millis(100), move || {
+            x.load(Ordering::Relaxed) < 10
+        });
+    }
+
+    #[tokio::test]
+    async fn it_never_becomes_true() {
+        let x = 1;
+        let y = 2;
+
+        deadline_never!(Duration::from_millis(10), move || x == y);
+    }
+
+    #[tokio::test]
+    #[should_panic(expected = "the condition became true after")]
+    async fn it_fails_when_the_condition_becomes_true() {
+        let errored = Arc::new(AtomicBool::new(false));
+
+        let errored_clone = errored.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+            errored_clone.store(true, Ordering::SeqCst);
+        });
+
+        deadline_never!(Duration::from_millis(100), move || {
+            errored.load(Ordering::Relaxed)
+        });
+    }
+
+    #[tokio::test]
+    async fn it_receives_a_message() {
+        let (tx, mut rx) = tokio::sync::mpsc::channel(1);
+
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+            tx.send(42).await.unwrap();
+        });
+
+        assert_eq!(deadline_recv!(Duration::from_millis(100), rx), 42);
+    }
+
+    #[tokio::test]
+    #[should_panic(expected = "the deadline has elapsed waiting for a message on: rx")]
+    async fn it_times_out_without_a_message() {
+        let (_tx, mut rx) = tokio::sync::mpsc::channel::<()>(1);
+
+        deadline_recv!(Duration::from_millis(1), rx);
+    }
+
+    #[tokio::test]
+    #[should_panic(expected = "the channel was closed before a message arrived on: rx")]
+    async fn it_fails_when_the_channel_is_closed() {
+        let (tx, mut rx) = tokio::sync::mpsc::channel::<()>(1);
+        drop(tx);
+
+        deadline_recv!(Duration::from_millis(100), rx);
+    }
+
+    #[tokio::test]
+    async fn it_reports_the_time_waited_for_a_message() {
+        let blocked = Cell::new(false);
+        // Blocks past the wait limit on the first poll, before the timer gets a chance to fire.
+        let recv = core::future::poll_fn(|_| {
+            if !blocked.replace(true) {
+                std::thread::sleep(Duration::from_millis(20));
+            }
+            Poll::<Option<()>>::Pending
+        });
+
+        let error = deadline_recv_inner(Duration::from_millis(1), recv)
+            .await
+            .unwrap_err();
+        assert!(error.waited() >= Duration::from_millis(20), "{error}");
+        assert_eq!(error.polls(), 1);
+    }
+
+    /// A stream of the items left in the vector, ending once it's empty.
+    #[cfg(feature = "stream")]
+    struct Items(Vec<u32>);
+
+    #[cfg(feature = "stream")]
+    impl futures_core::Stream for Items {
+        type Item = u32;
+
+        fn poll_next(
+            self: core::pin::Pin<&mut Self>,
+            _: &mut core::task::Context<'_>,
+        ) -> Poll<Option<u32>> {
+            Poll::Ready(self.get_mut().0.pop())
+        }
+    }
+
+    /// A stream which never yields an item.
+    #[cfg(feature = "stream")]
+    struct Silent;
+
+    #[cfg(feature = "stream")]
+    impl futures_core::Stream for Silent {
+        type Item = ();
+
+        fn poll_next(
+            self: core::pin::Pin<&mut Self>,
+            _: &mut core::task::Context<'_>,
+        ) -> Poll<Option<()>> {
+            Poll::Pending
+        }
+    }
+
+    #[cfg(feature = "stream")]
+    #[tokio::test]
+    async fn it_receives_the_next_item_of_a_stream() {
+        let mut items = Items(vec![2, 1]);
+
+        assert_eq!(deadline_recv!(Duration::from_millis(100), stream: items), 1);
+        assert_eq!(deadline_recv!(Duration::from_millis(100), stream: items), 2);
+    }
+
+    #[cfg(feature = "stream")]
+    #[tokio::test]
+    #[should_panic(expected = "the deadline has elapsed waiting for an item on: silent")]
+    async fn it_times_out_without_an_item() {
+        let mut silent = Silent;
+
+        deadline_recv!(Duration::from_millis(1), stream: silent);
+    }
+
+    #[cfg(feature = "stream")]
+    #[tokio::test]
+    #[should_panic(expected = "the stream ended before an item arrived on: items")]
+    async fn it_fails_when_the_stream_ends() {
+        let mut items = Items(Vec::new());
+
+        deadline_recv!(Duration::from_millis(100), stream: items);
+    }
+
+    #[cfg(feature = "tokio")]
+    #[tokio::test(start_paused = true)]
+    async fn it_advances_paused_time() {
+        let x = Arc::new(AtomicI32::new(41));
+        let y = 42;
+
+        let x_clone = x.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_secs(1)).await;
+            x_clone.fetch_add(1, Ordering::SeqCst);
+        });
+
+        let real = std::time::Instant::now();
+        let virtual_ = tokio::time::Instant::now();
+
+        let x_clone = x.clone();
+        deadline!(Duration::from_secs(60), move || {
+            x_clone.load(Ordering::Relaxed) == y
+        });
+        deadline_backoff!(Duration::from_secs(60), move || {
+            x.load(Ordering::Relaxed) == y
+        });
+
+        assert!(virtual_.elapsed() >= Duration::from_secs(1));
+        assert!(virtual_.elapsed() < Duration::from_secs(2));
+        assert!(real.elapsed() < Duration::from_secs(1));
+    }
+
+    #[cfg(feature = "glommio")]
+    #[test]
+    fn it_waits_on_a_glommio_executor() {
+        glommio::LocalExecutor::default().run(async {
+            let x = std::rc::Rc::new(Cell::new(41));
+
+            let x_clone = x.clone();
+            glommio::spawn_local(async move {
+                glommio::timer::sleep(Duration::from_millis(5)).await;
+                x_clone.set(42);
+            })
+            .detach();
+
+            deadline!(Duration::from_millis(100), move || x.get() == 42);
+            assert!(try_deadline!(Duration::from_millis(5), || false).is_err());
+        });
+    }
+
+    // Glommio's timers are tied to the thread they were created on.
+    #[cfg(not(feature = "glommio"))]
+    #[tokio::test(flavor = "multi_thread")]
+    async fn it_waits_in_a_spawned_task() {
+        let x = Arc::new(AtomicI32::new(41));
+        let y = 42;
+
+        let x_clone = x.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+            x_clone.fetch_add(1, Ordering::SeqCst);
+        });
+
+        tokio::spawn(async move {
+            deadline!(Duration::from_millis(100), move || {
+                x.load(Ordering::Relaxed) == y
+            });
+            deadline_with_interval!(Duration::from_millis(100), Duration::ZERO, || true);
+            deadline_value!(Duration::from_millis(100), || Some(()));
+        })
+        .await
+        .unwrap();
+    }
+
+    #[test]
+    fn it_reports_the_call_site_on_timeout() {
+        use std::{
+            panic::{self, AssertUnwindSafe},
+            sync::Mutex,
+        };
+
+        static LOCATION: Mutex<Option<(String, u32)>> = Mutex::new(None);
+
+        let previous = panic::take_hook();
+        panic::set_hook(Box::new(move |info| {
+            let message = info
+                .payload()
+                .downcast_ref::<String>()
+                .map(String::as_str)
+                .unwrap_or_default();
+            if let (true, Some(location)) = (message.contains("call_site_marker"), info.location())
+            {
+                *LOCATION.lock().unwrap() = Some((location.file().to_owned(), location.line()));
+            }
+            previous(info);
+        }));
+
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_time()
+            .build()
+            .unwrap();
+        let call_site_marker = false;
+
+        let expected_line = line!() + 3;
+        let result = panic::catch_unwind(AssertUnwindSafe(|| {
+            runtime.block_on(async {
+                deadline!(Duration::from_millis(1), move || call_site_marker);
+            })
+        }));
+
+        assert!(result.is_err());
+        assert_eq!(
+            LOCATION.lock().unwrap().take(),
+            Some((file!().to_owned(), expected_line))
+        );
+    }
+
+    /// Advances a fake clock by the requested duration on each sleep, without waiting.
+    struct MockSleeper {
+        now: Cell<std::time::Instant>,
+        sleeps: RefCell<Vec<Duration>>,
+    }
+
+    impl MockSleeper {
+        fn new() -> Self {
+            Self {
+                now: Cell::new(std::time::Instant::now()),
+                sleeps: RefCell::new(Vec::new()),
+            }
+        }
+
+        fn sleeps(&self) -> Vec<Duration> {
+            self.sleeps.borrow().clone()
+        }
+    }
+
+    impl Sleeper for MockSleeper {
+        fn now(&self) -> std::time::Instant {
+            self.now.get()
+        }
+
+        async fn sleep(&self, duration: Duration) {
+            self.sleeps.borrow_mut().push(duration);
+            self.now.set(self.now.get() + duration);
+        }
+    }
+
+    fn millis(millis: &[u64]) -> Vec<Duration> {
+        millis.iter().copied().map(Duration::from_millis).collect()
+    }
+
+    #[tokio::test]
+    async fn it_sleeps_for_the_interval_up_to_the_deadline() {
+        let sleeper = MockSleeper::new();
+
+        let result = deadline_inner_with_interval(
+            &sleeper,
+            Duration::from_millis(10),
+            Duration::from_millis(3),
+            || false,
+        )
+        .await;
+
+        assert!(result.is_err());
+        assert_eq!(sleeper.sleeps(), millis(&[3, 3, 3, 1]));
+    }
+
+    #[tokio::test]
+    async fn it_doubles_the_backoff_up_to_the_cap() {
+        let sleeper = MockSleeper::new();
+
+        let result = deadline_inner_with_backoff(
+            &sleeper,
+            Duration::from_millis(100),
+            Duration::from_millis(1),
+            Duration::from_millis(16),
+            || false,
+        )
+        .await;
+
+        assert!(result.is_err());
+        assert_eq!(
+            sleeper.sleeps(),
+            millis(&[1, 2, 4, 8, 16, 16, 16, 16, 16, 5])
+        );
+    }
+
+    #[tokio::test]
+    async fn it_restarts_the_settle_period_on_flaps() {
+        let sleeper = MockSleeper::new();
+        let calls = Cell::new(0);
+
+        // True on the 2nd to 4th evaluations, false on the 5th, then true for good.
+        let result = deadline_settle_inner(
+            &sleeper,
+            Duration::from_millis(100),
+            Duration::from_millis(3),
+            || {
+                calls.set(calls.get() + 1);
+                calls.get() != 1 && calls.get() != 5
+            },
+        )
+        .await;
+
+        assert!(result.is_ok());
+        // Settled 3ms after the condition became true again on the 6th evaluation.
+        assert_eq!(calls.get(), 9);
+    }
+
+    #[tokio::test]
+    async fn it_tells_flapping_from_never_true() {
+        let sleeper = MockSleeper::new();
+        let calls = Cell::new(0);
+
+        let flapping = deadline_settle_inner(
+            &sleeper,
+            Duration::from_millis(10),
+            Duration::from_millis(3),
+            || {
+                calls.set(calls.get() + 1);
+                calls.get() % 2 == 0
+            },
+        )
+        .await;
+        let never = deadline_settle_inner(
+            &sleeper,
+            Duration::from_millis(10),
+            Duration::from_millis(3),
+            || false,
+        )
+        .await;
+
+        assert!(matches!(flapping, Err((Some(5), _))));
+        assert!(matches!(never, Err((None, _))));
+    }
+
+    #[tokio::test]
+    async fn it_shortens_the_intervals_to_fit_the_min_polls() {
+        let sleeper = MockSleeper::new();
+
+        let error = Deadline::new(Duration::from_millis(10))
+            .poll_interval(Duration::from_secs(1))
+            .min_polls(5)
+            .sleeper(&sleeper)
+            .wait(|| false)
+            .await
+            .unwrap_err();
+
+        assert_eq!(sleeper.sleeps(), [Duration::from_micros(2500); 4].to_vec());
+        assert_eq!(error.polls(), 5);
+        assert!(error
+            .to_string()
+            .ends_with("after 10ms and 5 polls (at least 5 requested)"));
+    }
+
+    #[tokio::test]
+    async fn it_makes_the_min_polls_despite_oversleeping() {
+        /// Rounds every timer up to the next 10ms, like a coarse OS timer.
+        struct Coarse(MockSleeper);
+
+        impl Sleeper for Coarse {
+            fn now(&self) -> std::time::Instant {
+                self.0.now()
+            }
+
+            async fn sleep(&self, duration: Duration) {
+                self.0.sleeps.borrow_mut().push(duration);
+                let ticks = duration.as_micros().div_ceil(10_000) as u32;
+                self.0
+                    .now
+                    .set(self.0.now() + Duration::from_millis(10) * ticks);
+            }
+        }
+
+        let sleeper = Coarse(MockSleeper::new());
+
+        let error = Deadline::new(Duration::from_millis(5))
+            .min_polls(4)
+            .sleeper(&sleeper)
+            .wait(|| false)
+            .await
+            .unwrap_err();
+
+        assert_eq!(error.polls(), 4);
+        // The first sleep overshoots the deadline, so the others yield rather than sleep.
+        assert_eq!(sleeper.0.sleeps(), millis(&[1, 0, 0]));
+    }
+
+    #[tokio::test]
+    async fn it_stops_sleeping_once_true() {
+        let sleeper = MockSleeper::new();
+        let calls = Cell::new(0);
+
+        let result = deadline_inner_with_backoff(
+            &sleeper,
+            Duration::from_millis(100),
+            Duration::from_millis(1),
+            Duration::from_millis(16),
+            || {
+                calls.set(calls.get() + 1);
+                calls.get() == 3
+            },
+        )
+        .await;
+
+        assert!(result.is_ok());
+        assert_eq!(sleeper.sleeps(), millis(&[1, 2]));
+    }
+
+    #[tokio::test]
+    async fn it_reports_how_long_the_condition_held() {
+        let sleeper = MockSleeper::new();
+        let calls = Cell::new(0);
+
+        let result = deadline_sustained_inner(&sleeper, Duration::from_millis(100), || {
+            calls.set(calls.get() + 1);
+            calls.get() < 4
+        })
+        .await;
+
+        assert_eq!(result, Err((Duration::from_millis(3), 4)));
+    }
+
+    #[tokio::test]
+    #[should_panic(expected = "the deadline has elapsed for condition: x == y")]
+    async fn it_times_out_with_interval() {
+        let x = 1;
+        let y = 2;
+
+        deadline_with_interval!(
+            Duration::from_millis(10),
+            Duration::from_millis(2),
+            move || x == y
+        );
+    }
+
+    #[tokio::test]
+    async fn it_sleeps_between_evaluations() {
+        let calls = Arc::new(AtomicI32::new(0));
+
+        let calls_clone = calls.clone();
+        let result = deadline_inner_with_interval(
+            &RuntimeSleeper,
+            Duration::from_millis(50),
+            Duration::from_millis(20),
+            move || calls_clone.fetch_add(1, Ordering::SeqCst) == 4,
+        )
+        .await;
+
+        // Evaluations at 0, 20, 40 and 50ms, the fifth can't fit.
+        assert!(result.is_err());
+        assert!(calls.load(Ordering::SeqCst) <= 4);
+    }
+
+    #[tokio::test]
+    async fn it_spins_with_a_zero_interval() {
+        let x = Arc::new(AtomicI32::new(41));
+        let y = 42;
+
+        let x_clone = x.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+            x_clone.fetch_add(1, Ordering::SeqCst);
+        });
+
+        deadline_with_interval!(Duration::from_millis(100), Duration::ZERO, move || {
+            x.load(Ordering::Relaxed) == y
+        });
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn it_spins_on_the_spin_sleeper() {
+        let x = Arc::new(AtomicI32::new(41));
+
+        let x_clone = x.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+            x_clone.fetch_add(1, Ordering::SeqCst);
+        });
+
+        let outcome = Deadline::new(Duration::from_millis(100))
+            .poll_interval(Duration::ZERO)
+            .sleeper(SpinSleeper)
+            .wait(move || x.load(Ordering::Relaxed) == 42)
+            .await
+            .unwrap();
+
+        assert!(outcome.polls > 1);
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn it_lets_spawned_tasks_run_on_a_current_thread_runtime() {
+        let x = Arc::new(AtomicI32::new(41));
+
+        let x_clone = x.clone();
+        tokio::spawn(async move {
+            tokio::task::yield_now().await;
+            tokio::time::sleep(Duration::from_millis(5)).await;
+            x_clone.fetch_add(1, Ordering::SeqCst);
+        });
+
+        let x_clone = x.clone();
+        deadline_with_interval!(Duration::from_secs(1), Duration::ZERO, move || {
+            x_clone.load(Ordering::Relaxed) == 42
+        });
+
+        let x_clone = x.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+            x_clone.fetch_add(1, Ordering::SeqCst);
+        });
+
+        Deadline::new(Duration::from_secs(1))
+            .poll_interval(Duration::ZERO)
+            .sleeper(SpinSleeper)
+            .wait(move || x.load(Ordering::Relaxed) == 43)
+            .await
+            .unwrap();
+    }
+
+    #[cfg(feature = "log")]
+    #[tokio::test]
+    async fn it_logs_the_wait_and_the_timeout() {
+        struct Recording(Mutex<Vec<(log::Level, String)>>);
+
+        impl log::Log for Recording {
+            fn enabled(&self, _: &log::Metadata<'_>) -> bool {
+                true
+            }
+
+            fn log(&self, record: &log::Record<'_>) {
+                let entry = (record.level(), record.args().to_string());
+                self.0.lock().unwrap().push(entry);
+            }
+
+            fn flush(&self) {}
+        }
+
+        static RECORDING: Recording = Recording(Mutex::new(Vec::new()));
+        log::set_logger(&RECORDING).unwrap();
+        log::set_max_level(log::LevelFilter::Debug);
+
+        let x = AtomicI32::new(41);
+        let error = try_deadline!(Duration::from_millis(5), || x.load(Ordering::Relaxed) == 42)
+            .unwrap_err();
+
+        let records = RECORDING.0.lock().unwrap();
+        assert!(records.contains(&(
+            log::Level::Debug,
+            "waiting up to 5ms for condition: x.load(Ordering::Relaxed) == 42".to_owned()
+        )));
+        let polls = format!(" and {} polls", error.polls());
+        assert!(records
+            .iter()
+            .any(|(level, message)| *level == log::Level::Warn
+                && message.starts_with("the deadline has elapsed after ")
+                && message.ends_with(&polls)));
+    }
+
+    #[cfg(feature = "tracing")]
+    #[tokio::test]
+    async fn it_emits_tracing_events() {
+        use core::sync::atomic::AtomicUsize;
+        use tracing::{span, Event, Metadata, Subscriber};
+
+        #[derive(Default)]
+        struct Counting {
+            spans: AtomicUsize,
+            events: AtomicUsize,
+        }
+
+        impl Subscriber for &'static Counting {
+            fn enabled(&self, _: &Metadata<'_>) -> bool {
+                true
+            }
+
+            fn new_span(&self, _: &span::Attributes<'_>) -> span::Id {
+                span::Id::from_u64(self.spans.fetch_add(1, Ordering::SeqCst) as u64 + 1)
+            }
+
+            fn record(&self, _: &span::Id, _: &span::Record<'_>) {}
+
+            fn record_follows_from(&self, _: &span::Id, _: &span::Id) {}
+
+            fn event(&self, _: &Event<'_>) {
+                self.events.fetch_add(1, Ordering::SeqCst);
+            }
+
+            fn enter(&self, _: &span::Id) {}
+
+            fn exit(&self, _: &span::Id) {}
+        }
+
+        let counting: &'static Counting = Box::leak(Box::default());
+        let _guard = tracing::subscriber::set_default(counting);
+
+        let calls = Cell::new(0);
+        deadline!(Duration::from_secs(1), || {
+            calls.set(calls.get() + 1);
+            calls.get() == 3
+        });
+
+        assert_eq!(counting.spans.load(Ordering::SeqCst), 1);
+        // One event per evaluation and one for the outcome.
+        assert_eq!(counting.events.load(Ordering::SeqCst), 4);
+    }
+
+    #[cfg(all(feature = "tokio", feature = "tracing"))]
+    #[tokio::test]
+    async fn it_evaluates_spawn_blocking_conditions_within_the_current_span() {
+        use tracing::{span, Event, Instrument, Metadata, Subscriber};
+        use tracing_core::span::Current;
+
+        thread_local! {
+            static ENTERED: RefCell<Vec<u64>> = const { RefCell::new(Vec::new()) };
+        }
+
+        /// The metadata and parent of each span, indexed by id.
+        #[derive(Default)]
+        struct Spans(Mutex<Vec<(&'static Metadata<'static>, Option<u64>)>>);
+
+        impl Spans {
+            /// Returns the names of the span entered on this thread and of its ancestors.
+            fn entered(&self) -> Vec<&'static str> {
+                let spans = self.0.lock().unwrap();
+                let mut id = ENTERED.with(|entered| entered.borrow().last().copied());
+                iter::from_fn(|| {
+                    let (metadata, parent) = spans[usize::try_from(id? - 1).unwrap()];
+                    id = parent;
+                    Some(metadata.name())
+                })
+                .collect()
+            }
+        }
+
+        impl Subscriber for &'static Spans {
+            fn enabled(&self, _: &Metadata<'_>) -> bool {
+                true
+            }
+
+            fn new_span(&self, attributes: &span::Attributes<'_>) -> span::Id {
+                let parent = attributes.parent().map(span::Id::into_u64).or_else(|| {
+                    ENTERED
+                        .with(|entered| entered.borrow().last().copied())
+                        .filter(|_| attributes.is_contextual())
+                });
+                let mut spans = self.0.lock().unwrap();
+                spans.push((attributes.metadata(), parent));
+                span::Id::from_u64(u64::try_from(spans.len()).unwrap())
+            }
+
+            fn record(&self, _: &span::Id, _: &span::Record<'_>) {}
+
+            fn record_follows_from(&self, _: &span::Id, _: &span::Id) {}
+
+            fn event(&self, _: &Event<'_>) {}
+
+            fn enter(&self, id: &span::Id) {
+                ENTERED.with(|entered| entered.borrow_mut().push(id.into_u64()));
+            }
+
+            fn exit(&self, _: &span::Id) {
+                ENTERED.with(|entered| entered.borrow_mut().pop());
+            }
+
+            fn current_span(&self) -> Current {
+                match ENTERED.with(|entered| entered.borrow().last().copied()) {
+                    Some(id) => {
+                        let (metadata, _) =
+                            self.0.lock().unwrap()[usize::try_from(id - 1).unwrap()];
+                        Current::new(span::Id::from_u64(id), metadata)
+                    }
+                    None => Current::none(),
+                }
+            }
+        }
+
+        let spans: &'static Spans = Box::leak(Box::default());
+        let _guard = tracing::subscriber::set_default(spans);
+        let test_thread = std::thread::current().id();
+
+        async {
+            deadline_spawn_blocking!(Duration::from_secs(1), move || {
+                std::thread::current().id() != test_thread
+                    && spans.entered() == ["deadline", "outer"]
+            });
+        }
+        .instrument(tracing::info_span!("outer"))
+        .await;
+    }
+
+    #[tokio::test]
+    async fn it_stops_after_max_polls() {
+        let sleeper = MockSleeper::new();
+        let calls = Cell::new(0);
+
+        let result = Deadline::new(Duration::MAX)
+            .poll_interval(Duration::ZERO)
+            .max_polls(3)
+            .sleeper(&sleeper)
+            .wait(|| {
+                calls.set(calls.get() + 1);
+                false
+            })
+            .await;
+
+        assert!(matches!(
+            result,
+            Err(DeadlineError::PollsExhausted { polls: 3, .. })
+        ));
+        assert_eq!(calls.get(), 3);
+        // Yields between evaluations without sleeping.
+        assert_eq!(sleeper.sleeps(), millis(&[0, 0]));
+    }
+
+    #[tokio::test]
+    async fn it_reports_the_message_and_polls_on_timeout() {
+        let sleeper = MockSleeper::new();
+
+        let error = Deadline::new(Duration::from_millis(10))
+            .poll_interval(Duration::from_millis(4))
+            .message("never")
+            .sleeper(&sleeper)
+            .wait(|| false)
+            .await
+            .unwrap_err();
+
+        // Evaluations at 0, 4, 8 and 10ms.
+        assert_eq!(error.polls(), 4);
+        assert_eq!(error.waited(), Duration::from_millis(10));
+        assert_eq!(
+            error.to_string(),
+            "never: deadline of 10ms has elapsed after 10ms and 4 polls"
+        );
+    }
+
+    #[tokio::test]
+    #[should_panic(expected = "the condition was still false after 5 polls: x == y")]
+    async fn it_reports_the_number_of_polls() {
+        let x = 1;
+        let y = 2;
+
+        deadline_polls!(5, move || x == y);
+    }
+
+    #[tokio::test]
+    async fn it_accumulates_state_across_evaluations() {
+        let mut calls = 0;
+
+        deadline_mut!(Duration::from_secs(1), move || {
+            calls += 1;
+            calls == 3
+        });
+    }
+
+    #[tokio::test]
+    async fn it_evaluates_an_fn_mut_until_true() {
+        let sleeper = MockSleeper::new();
+        let mut calls = 0;
+
+        let result = Deadline::new(Duration::from_secs(1))
+            .sleeper(&sleeper)
+            .wait(|| {
+                calls += 1;
+                calls == 3
+            })
+            .await;
+
+        assert_eq!(
+            result.unwrap(),
+            DeadlineOutcome {
+                polls: 3,
+                elapsed: Duration::from_millis(2),
+                timed_out: false,
+            }
+        );
+        assert_eq!(calls, 3);
+    }
+
+    #[cfg(feature = "tokio")]
+    #[tokio::test]
+    async fn it_delays_missed_ticks_of_a_steady_interval() {
+        let evaluations = RefCell::new(Vec::new());
+        let condition = || {
+            let mut evaluations = evaluations.borrow_mut();
+            evaluations.push(std::time::Instant::now());
+            if evaluations.len() == 2 {
+                // Outlast a few periods.
+                std::thread::sleep(Duration::from_millis(35));
+            }
+            evaluations.len() == 5
+        };
+
+        let outcome = Deadline::new(Duration::from_secs(1))
+            .interval_steady(Duration::from_millis(10))
+            .wait(condition)
+            .await
+            .unwrap();
+
+        assert_eq!(outcome.polls, 5);
+        let evaluations = evaluations.into_inner();
+        for pair in evaluations.windows(2) {
+            assert!(pair[1] - pair[0] >= Duration::from_millis(5));
+        }
+    }
+
+    #[tokio::test]
+    async fn it_aligns_evaluations_to_the_anchor() {
+        let sleeper = MockSleeper::new();
+        let anchor = sleeper.now();
+        let evaluations = RefCell::new(Vec::new());
+
+        let outcome = Deadline::new(Duration::from_secs(1))
+            .interval_aligned(anchor, Duration::from_millis(10))
+            .sleeper(&sleeper)
+            .wait(|| {
+                let mut evaluations = evaluations.borrow_mut();
+                evaluations.push(sleeper.now() - anchor);
+                // Slow evaluations, the third outlasting the next two ticks.
+                match evaluations.len() {
+                    2 => sleeper.now.set(sleeper.now() + Duration::from_millis(3)),
+                    3 => sleeper.now.set(sleeper.now() + Duration::from_millis(25)),
+                    _ => {}
+                }
+                evaluations.len() == 4
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(outcome.polls, 4);
+        assert_eq!(evaluations.into_inner(), millis(&[0, 10, 20, 50]));
+        assert_eq!(sleeper.sleeps(), millis(&[10, 7, 5]));
+    }
+
+    #[tokio::test]
+    async fn it_reports_the_last_snapshots_on_timeout() {
+        let sleeper = MockSleeper::new();
+        let x = Cell::new(0);
+
+        let error = Deadline::new(Duration::from_millis(4))
+            .sleeper(&sleeper)
+            .message("x never reached 10")
+            .record_history(3, || x.get())
+            .wait(|| {
+                x.set(x.get() + 1);
+                x.get() == 10
+            })
+            .await
+            .unwrap_err();
+
+        assert_eq!(error.observed(), ["3", "4", "5"]);
+        assert_eq!(
+            error.to_string(),
+            "x never reached 10: deadline of 4ms has elapsed after 4ms and 5 polls, observed: \
+             [..., 3, 4, 5]"
+        );
+    }
+
+    #[tokio::test]
+    async fn it_runs_the_diagnostics_closure_on_timeout() {
+        let sleeper = MockSleeper::new();
+        let x = Cell::new(0);
+
+        let error = Deadline::new(Duration::from_millis(2))
+            .sleeper(&sleeper)
+            .on_timeout(|| format!("x stopped at {}", x.get()))
+            .wait(|| {
+                x.set(x.get() + 1);
+                false
+            })
+            .await
+            .unwrap_err();
+
+        assert_eq!(error.diagnostics(), Some("x stopped at 3"));
+        assert_eq!(
+            error.to_string(),
+            "deadline of 2ms has elapsed after 2ms and 3 polls\n\ndiagnostics:\nx stopped at 3"
+        );
+    }
+
+    #[tokio::test]
+    async fn it_tells_timeouts_from_terminal_errors() {
+        let sleeper = MockSleeper::new();
+        let calls = Cell::new(0);
+
+        let timed_out = Deadline::new(Duration::from_millis(2))
+            .sleeper(&sleeper)
+            .wait(|| false)
+            .await
+            .unwrap_err();
+        let terminal = Deadline::new(Duration::from_millis(10))
+            .sleeper(&sleeper)
+            .message("server ready")
+            .try_wait(|| {
+                calls.set(calls.get() + 1);
+                if calls.get() < 3 {
+                    Ok(false)
+                } else {
+                    Err("the server exited")
+                }
+            })
+            .await
+            .unwrap_err();
+
+        assert!(timed_out.is_timed_out() && !timed_out.is_terminal());
+        assert!(terminal.is_terminal() && !terminal.is_timed_out() && !terminal.is_cancelled());
+        assert!(matches!(terminal, DeadlineError::Terminal { polls: 3, .. }));
+        assert_eq!(
+            terminal.to_string(),
+            "server ready: condition failed after 3 polls and 2ms: the server exited"
+        );
+        assert_eq!(
+            std::error::Error::source(&terminal).map(ToString::to_string),
+            Some("the server exited".to_owned())
+        );
+        assert!(std::error::Error::source(&timed_out).is_none());
+        let DeadlineError::Terminal { error, .. } = terminal else {
+            unreachable!()
+        };
+        assert_eq!(error.to_string(), "the server exited");
+    }
+
+    #[cfg(feature = "tokio-util")]
+    #[tokio::test]
+    async fn it_returns_cancelled_once_the_token_is_cancelled() {
+        let token = tokio_util::sync::CancellationToken::new();
+
+        let token_clone = token.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+            token_clone.cancel();
+        });
+
+        let error = Deadline::new(Duration::from_secs(10))
+            .cancel_on(token)
+            .wait(|| false)
+            .await
+            .unwrap_err();
+
+        assert!(error.is_cancelled() && !error.is_timed_out());
+        assert!(matches!(error, DeadlineError::Cancelled { polls, .. } if polls > 1));
+    }
+
+    #[tokio::test]
+    async fn it_sleeps_longer_after_expensive_evaluations() {
+        let sleeper = MockSleeper::new();
+
+        let error = Deadline::new(Duration::from_millis(20))
+            .sleeper(&sleeper)
+            .back_pressure(Duration::from_millis(2), 2.0)
+            .wait(|| {
+                sleeper
+                    .now
+                    .set(sleeper.now.get() + Duration::from_millis(4));
+                false
+            })
+            .await
+            .unwrap_err();
+
+        assert_eq!(sleeper.sleeps(), millis(&[8, 4]));
+        assert_eq!(error.evaluating(), Some(Duration::from_millis(12)));
+        assert_eq!(
+            error.to_string(),
+            "deadline of 20ms has elapsed after 24ms and 3 polls, spending 12ms evaluating the \
+             condition and 12ms sleeping"
+        );
+    }
+
+    #[tokio::test]
+    async fn it_skips_the_diagnostics_closure_on_success() {
+        let called = Cell::new(false);
+
+        let result = Deadline::new(Duration::from_millis(10))
+            .on_timeout(|| called.set(true))
+            .wait(|| true)
+            .await;
+
+        assert!(result.is_ok());
+        assert!(!called.get());
+    }
+
+    #[cfg(feature = "tokio")]
+    #[tokio::test(start_paused = true)]
+    async fn it_evaluates_to_the_time_taken() {
+        let started = Instant::now();
+        let condition = || started.elapsed() >= Duration::from_millis(20);
+
+        let elapsed = deadline_timed!(Duration::from_secs(1), condition);
+
+        // Paused time only advances by whole poll intervals.
+        assert_eq!(elapsed, Duration::from_millis(20));
+    }
+
+    #[tokio::test]
+    async fn it_checks_before_or_after_the_first_interval() {
+        for (check_first, expected) in [(true, [0, 10, 20]), (false, [10, 20, 30])] {
+            let sleeper = MockSleeper::new();
+            let started = sleeper.now();
+            let mut evaluations = Vec::new();
+
+            Deadline::new(Duration::from_secs(1))
+                .poll_interval(Duration::from_millis(10))
+                .check_first(check_first)
+                .sleeper(&sleeper)
+                .wait(|| {
+                    evaluations.push(sleeper.now() - started);
+                    evaluations.len() == 3
+                })
+                .await
+                .unwrap();
+
+            assert_eq!(evaluations, millis(&expected));
+        }
+    }
+
+    #[tokio::test]
+    async fn it_retries_until_true() {
+        let mut attempts = 0;
+
+        let (reported, elapsed) = deadline_retry!(Duration::from_secs(1), 10, || {
+            attempts += 1;
+            attempts == 3
+        });
+
+        assert_eq!(reported, 3);
+        // Two backoffs, of 1ms and 2ms.
+        assert!(elapsed >= Duration::from_millis(3));
+    }
+
+    #[cfg(feature = "tokio")]
+    #[tokio::test(start_paused = true)]
+    #[should_panic(
+        expected = "the condition was still false after 4 attempts and 7ms: x == y (at most 4 \
+                    attempts within 1s)"
+    )]
+    async fn it_stops_retrying_after_the_max_attempts() {
+        let x = 1;
+        let y = 2;
+
+        deadline_retry!(Duration::from_secs(1), 4, move || x == y);
+    }
+
+    #[tokio::test]
+    async fn it_counts_every_evaluation_in_the_outcome() {
+        let sleeper = MockSleeper::new();
+        let calls = Cell::new(0);
+        let condition = || {
+            calls.set(calls.get() + 1);
+            calls.get() == 4
+        };
+
+        let outcome = Deadline::new(Duration::from_secs(1))
+            .backoff(Duration::from_millis(1), Duration::from_millis(4))
+            .warmup(Duration::from_millis(2))
+            .sleeper(&sleeper)
+            .wait(condition)
+            .await
+            .unwrap();
+
+        assert_eq!(outcome.polls, 4);
+        assert_eq!(calls.get(), 4);
+
+        let outcome = Deadline::new(Duration::from_secs(1))
+            .sleeper(&sleeper)
+            .wait(|| true)
+            .await
+            .unwrap();
+
+        assert_eq!(outcome.polls, 1);
+    }
+
+    #[tokio::test]
+    #[should_panic(expected = "(last = 1.5, target = 1.0, epsilon = 0.1) after 1ms")]
+    async fn it_reports_the_last_value_outside_the_tolerance() {
+        deadline_approx!(Duration::from_millis(1), || 1.5, 1.0, 0.1);
+    }
+
+    #[tokio::test]
+    async fn it_never_accepts_nan() {
+        let sleeper = MockSleeper::new();
+
+        let result = deadline_approx_inner(
+            &sleeper,
+            Duration::from_millis(2),
+            || f64::NAN,
+            1.0,
+            f64::INFINITY,
+        )
+        .await;
+
+        assert!(result.is_err_and(|(last, _)| last.is_some_and(f64::is_nan)));
+    }
+
+    #[cfg(feature = "tokio-util")]
+    #[tokio::test]
+    #[should_panic(expected = "the deadline was cancelled for condition: x == y after ")]
+    async fn it_gives_up_when_cancelled() {
+        let x = 1;
+        let y = 2;
+        let token = tokio_util::sync::CancellationToken::new();
+
+        let token_clone = token.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+            token_clone.cancel();
+        });
+
+        deadline_cancellable!(Duration::from_secs(10), token, move || x == y);
+    }
+
+    #[test]
+    fn it_waits_without_a_runtime() {
+        let x = Arc::new(AtomicBool::new(false));
+
+        let x_clone = x.clone();
+        std::thread::spawn(move || {
+            std::thread::sleep(Duration::from_millis(5));
+            x_clone.store(true, Ordering::SeqCst);
+        });
+
+        deadline_blocking!(Duration::from_secs(1), move || x.load(Ordering::Relaxed));
+    }
+
+    #[test]
+    #[should_panic(expected = "the deadline has elapsed for condition: x == y after 1ms (waited ")]
+    fn it_times_out_without_a_runtime() {
+        let x = 1;
+        let y = 2;
+
+        deadline_blocking!(Duration::from_millis(1), move || x == y);
+    }
+
+    #[test]
+    fn it_returns_whether_the_blocking_condition_was_met() {
+        let x = Arc::new(AtomicBool::new(false));
+
+        let x_clone = x.clone();
+        std::thread::spawn(move || {
+            std::thread::sleep(Duration::from_millis(5));
+            x_clone.store(true, Ordering::SeqCst);
+        });
+
+        assert!(deadline_blocking_bool(Duration::from_secs(1), move || x.load(Ordering::Relaxed)));
+    }
+
+    #[test]
+    fn it_returns_false_when_the_blocking_condition_times_out() {
+        let polls = Cell::new(0);
+
+        let started = std::time::Instant::now();
+        assert!(!deadline_blocking_bool(Duration::from_millis(5), || {
+            polls.set(polls.get() + 1);
+            false
+        }));
+        assert!(started.elapsed() >= Duration::from_millis(5));
+        assert!(polls.get() > 1);
+    }
+
+    #[tokio::test]
+    async fn it_passes_the_context_to_the_condition() {
+        struct Counter {
+            count: u32,
+        }
+
+        impl Counter {
+            fn tick(&mut self) -> bool {
+                self.count += 1;
+                self.count == 3
+            }
+        }
+
+        let mut counter = Counter { count: 0 };
+
+        deadline_ctx!(Duration::from_secs(1), &mut counter, |counter| {
+            counter.tick()
+        });
+
+        // The borrow ends with the macro, so the context can be used again.
+        assert_eq!(counter.count, 3);
+        counter.count = 0;
+        deadline_ctx!(Duration::from_secs(1), &mut counter, Counter::tick);
+    }
+
+    #[tokio::test]
+    #[should_panic(expected = "the deadline has elapsed for condition: |ready| *ready")]
+    async fn it_times_out_with_a_context() {
+        let mut ready = false;
+
+        deadline_ctx!(Duration::from_millis(1), &mut ready, |ready| *ready);
+    }
+
+    #[tokio::test]
+    async fn it_waits_again_after_being_cancelled() {
+        let x = Arc::new(AtomicI32::new(0));
+        let calls = Arc::new(AtomicI32::new(0));
+
+        let (x_clone, calls_clone) = (x.clone(), calls.clone());
+        let waiting = Deadline::new(Duration::from_secs(1))
+            .record_history(4, move || x_clone.load(Ordering::Relaxed))
+            .wait(move || {
+                calls_clone.fetch_add(1, Ordering::SeqCst);
+                false
+            });
+        tokio::select! {
+            _ = waiting => panic!("the condition is never met"),
+            () = tokio::time::sleep(Duration::from_millis(10)) => {}
+        }
+
+        // Dropped while sleeping between evaluations, so no evaluation is left running.
+        let calls_when_dropped = calls.load(Ordering::SeqCst);
+        assert!(calls_when_dropped > 0);
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        assert_eq!(calls.load(Ordering::SeqCst), calls_when_dropped);
+
+        let x_clone = x.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+            x_clone.store(42, Ordering::SeqCst);
+        });
+
+        deadline!(Duration::from_secs(1), move || x.load(Ordering::Relaxed)
+            == 42);
+    }
+
+    #[tokio::test]
+    async fn it_only_polls_when_woken() {
+        let waker = Arc::new(Mutex::new(None::<core::task::Waker>));
+        let polls = AtomicI32::new(0);
+
+        let waker_clone = waker.clone();
+        tokio::spawn(async move {
+            for _ in 0..2 {
+                tokio::time::sleep(Duration::from_millis(5)).await;
+                if let Some(waker) = waker_clone.lock().unwrap().take() {
+                    waker.wake();
+                }
+            }
+        });
+
+        deadline_poll_fn!(Duration::from_secs(1), |cx| {
+            if polls.fetch_add(1, Ordering::SeqCst) == 2 {
+                return Poll::Ready(());
+            }
+            *waker.lock().unwrap() = Some(cx.waker().clone());
+            Poll::Pending
+        });
+
+        // Once upfront, then once per wake.
+        assert_eq!(polls.load(Ordering::SeqCst), 3);
+    }
+
+    // Without timers, the minimal backend polls the future each time it yields.
+    #[cfg(not(feature = "minimal"))]
+    #[tokio::test]
+    async fn it_times_out_when_never_woken() {
+        let error = deadline_poll_fn_inner(Duration::from_millis(10), |_| Poll::Pending)
+            .await
+            .unwrap_err();
+
+        // Once upfront, then once more when the deadline elapses.
+        assert_eq!(error.polls(), 2);
+        assert!(error.waited() >= Duration::from_millis(10));
+    }
+
+    #[cfg(feature = "test-util")]
+    #[tokio::test]
+    async fn it_forces_timeouts_until_the_guard_is_dropped() {
+        let calls = Cell::new(0);
+        let condition = || {
+            calls.set(calls.get() + 1);
+            true
+        };
+
+        {
+            let _forced = test_util::force_timeout();
+            let error = try_deadline!(Duration::from_secs(60), condition).unwrap_err();
+            assert_eq!((error.polls(), error.waited()), (0, Duration::ZERO));
+            assert!(deadline_blocking_inner(Duration::from_secs(60), condition).is_err());
+            assert!(
+                deadline_recv_inner(Duration::from_secs(60), async { Some(()) })
+                    .await
+                    .is_err()
+            );
+        }
+
+        assert_eq!(calls.get(), 0);
+        try_deadline!(Duration::from_secs(60), condition).unwrap();
+        assert_eq!(calls.get(), 1);
+    }
+
+    #[cfg(feature = "test-util")]
+    #[tokio::test]
+    async fn it_fails_sustained_conditions_when_forced_to_time_out() {
+        let sleeper = MockSleeper::new();
+        let calls = Cell::new(0);
+        let condition = || {
+            calls.set(calls.get() + 1);
+            true
+        };
+
+        let _forced = test_util::force_timeout();
+        let sustained = deadline_sustained_inner(&sleeper, Duration::from_secs(60), condition);
+        assert_eq!(sustained.await, Err((Duration::ZERO, 0)));
+        let never = deadline_never_inner(&sleeper, Duration::from_secs(60), || !condition());
+        assert_eq!(never.await, Err((Duration::ZERO, 0)));
+
+        assert_eq!(calls.get(), 0);
+        assert!(sleeper.sleeps().is_empty());
+    }
+
+    #[cfg(feature = "test-util")]
+    #[tokio::test]
+    #[should_panic(expected = "the deadline has elapsed for condition: x == y")]
+    async fn it_panics_when_forced_to_time_out() {
+        let x = 42;
+        let y = 42;
+
+        let _forced = test_util::force_timeout();
+        deadline!(Duration::from_secs(60), move || x == y);
+    }
+
+    #[cfg(feature = "test-util")]
+    #[tokio::test]
+    async fn it_reports_forced_timeouts_of_the_value_reporting_macros() {
+        static ATOMIC: AtomicUsize = AtomicUsize::new(0);
+
+        /// Returns what the waits the macro expands to panics with.
+        async fn panic_message(wait: impl Future<Output = ()> + 'static) -> String {
+            let local = tokio::task::LocalSet::new();
+            let task = local.spawn_local(wait);
+            let panic = local.run_until(task).await.unwrap_err().into_panic();
+            panic.downcast_ref::<String>().unwrap().clone()
+        }
+
+        // The local tasks run on this thread, where the timeouts are forced.
+        let _forced = test_util::force_timeout();
+        let wait_limit = Duration::from_secs(60);
+        let messages = [
+            (
+                panic_message(async move {
+                    deadline_ok!(wait_limit, || "x".parse::<u32>());
+                })
+                .await,
+                r#""x".parse::<u32>()"#,
+            ),
+            (
+                panic_message(async move { deadline_eq!(wait_limit, || 1, 2) }).await,
+                "1 == 2",
+            ),
+            (
+                panic_message(async move { deadline_eq_fn!(wait_limit, || 1, || 2) }).await,
+                "1 == 2",
+            ),
+            (
+                panic_message(async move { deadline_atomic!(wait_limit, &ATOMIC, 1) }).await,
+                "&ATOMIC == 1",
+            ),
+            (
+                panic_message(async move { deadline_ge!(wait_limit, || 1, 2) }).await,
+                "1 >= 2",
+            ),
+            (
+                panic_message(async move { deadline_le!(wait_limit, || 2, 1) }).await,
+                "2 <= 1",
+            ),
+            (
+                panic_message(async move { deadline_gt!(wait_limit, || 1, 2) }).await,
+                "1 > 2",
+            ),
+            (
+                panic_message(async move { deadline_lt!(wait_limit, || 2, 1) }).await,
+                "2 < 1",
+            ),
+            (
+                panic_message(async move { deadline_len!(wait_limit, || 0, >= 3) }).await,
+                "0 >= 3",
+            ),
+            (
+                panic_message(async move { deadline_approx!(wait_limit, || 1.0, 2.0, 0.1) }).await,
+                "1.0 ~= 2.0",
+            ),
+            (
+                panic_message(async move {
+                    deadline_progress!(wait_limit, || 0, |value| value > 5);
+                })
+                .await,
+                "|value| value > 5 on 0",
+            ),
+            (
+                panic_message(async move {
+                    deadline_monotonic!(wait_limit, || 0, 5);
+                })
+                .await,
+                "0 to reach 5",
+            ),
+        ];
+
+        for (message, condition) in messages {
+            let expected = format!("the deadline has elapsed for condition: {condition} after 60s");
+            assert!(message.starts_with(&expected), "{message}");
+        }
+    }
+
+    #[cfg(feature = "tokio")]
+    #[actix_rt::test]
+    async fn it_works_on_the_actix_runtime() {
+        let x = std::rc::Rc::new(Cell::new(41));
+
+        // Actix runs its tasks on a `LocalSet`, so they needn't be `Send`.
+        let x_clone = x.clone();
+        actix_rt::spawn(async move {
+            actix_rt::time::sleep(Duration::from_millis(5)).await;
+            x_clone.set(42);
+        });
+
+        deadline!(Duration::from_secs(1), move || x.get() == 42);
+    }
+
+    #[cfg(feature = "minimal")]
+    #[test]
+    fn it_waits_on_a_bare_executor_with_the_minimal_backend() {
+        use std::{
+            sync::Arc,
+            task::{Context, Poll, Wake, Waker},
+            thread::{self, Thread},
+        };
+
+        struct Unpark(Thread);
+
+        impl Wake for Unpark {
+            fn wake(self: Arc<Self>) {
+                self.0.unpark();
+            }
+        }
+
+        // The least an executor can do: poll the future on the current thread until it's ready.
+        fn block_on<F: Future>(future: F) -> F::Output {
+            let mut future = core::pin::pin!(future);
+            let waker = Waker::from(Arc::new(Unpark(thread::current())));
+            let mut cx = Context::from_waker(&waker);
+            loop {
+                if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+                    return output;
+                }
+                thread::park();
+            }
+        }
+
+        let started = Instant::now();
+        block_on(async {
+            deadline!(Duration::from_secs(1), || started.elapsed()
+                >= Duration::from_millis(5));
+            deadline_with_interval!(Duration::from_secs(1), Duration::from_millis(2), || {
+                started.elapsed() >= Duration::from_millis(10)
+            });
+        });
+        let error = block_on(async { try_deadline!(Duration::from_millis(5), || false) });
+        assert!(error.is_err());
+    }
+
+    #[tokio::test]
+    async fn it_evaluates_once_with_a_zero_wait_limit() {
+        let calls = AtomicI32::new(0);
+
+        deadline!(Duration::ZERO, || calls.fetch_add(1, Ordering::SeqCst) == 0);
+        deadline!(Duration::ZERO, || true, "with a message");
+        deadline_with_interval!(Duration::ZERO, Duration::from_secs(1), || true);
+        deadline_backoff!(Duration::ZERO, || true);
+        deadline_eq!(Duration::ZERO, || 42, 42);
+        deadline_blocking!(Duration::ZERO, || true);
+        assert!(matches!(
+            deadline_recv_inner(Duration::ZERO, async { Some(()) }).await,
+            Ok(Some(()))
+        ));
+        Deadline::new(Duration::ZERO)
+            .check_first(false)
+            .wait(|| true)
+            .await
+            .unwrap();
+        #[cfg(feature = "tokio")]
+        deadline_spawn_blocking!(Duration::ZERO, || true);
+
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    #[should_panic(
+        expected = "with a message: the deadline of 0 never allows waiting; condition was false at \
+                    t=0: x == y"
+    )]
+    async fn it_explains_a_false_condition_with_a_zero_wait_limit() {
+        let x = 1;
+        let y = 2;
+
+        deadline!(Duration::ZERO, move || x == y, "with a message");
+    }
+
+    #[tokio::test]
+    async fn it_times_out_deterministically_with_a_zero_wait_limit() {
+        for _ in 0..100 {
+            let calls = Cell::new(0);
+
+            let error = try_deadline!(Duration::ZERO, || {
+                calls.set(calls.get() + 1);
+                false
+            })
+            .unwrap_err();
+
+            assert_eq!(calls.get(), 1);
+            assert_eq!(error.polls(), 1);
+        }
+    }
+
+    #[tokio::test]
+    #[should_panic(
+        expected = r#"the deadline has elapsed for condition: { status == "done {final} {{}} {} 100%" }"#
+    )]
+    async fn it_renders_braces_in_the_condition_verbatim() {
+        let status = "pending";
+
+        deadline!(Duration::from_millis(1), move || {
+            status == "done {final} {{}} {} 100%"
+        });
+    }
+
+    #[tokio::test]
+    async fn it_renders_braces_in_the_error_verbatim() {
+        let status = "pending";
+
+        let error =
+            try_deadline!(Duration::from_millis(1), move || status == "{{}} {0} %s").unwrap_err();
+
+        assert_eq!(error.condition(), Some(r#"status == "{{}} {0} %s""#));
+        assert!(error
+            .to_string()
+            .contains(r#"for condition: status == "{{}} {0} %s" after"#));
+    }
+
+    #[tokio::test]
+    async fn it_takes_integer_literals_as_milliseconds() {
+        let x = Arc::new(AtomicI32::new(41));
+
+        let x_clone = x.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+            x_clone.fetch_add(1, Ordering::SeqCst);
+        });
+
+        deadline!(1000, move || x.load(Ordering::Relaxed) == 42);
+
+        let wait_limit = Duration::from_millis(1);
+        deadline!(wait_limit, || true);
+        deadline!(14, || true, "with a {}", "message");
+    }
+
+    #[tokio::test]
+    #[should_panic(expected = "the deadline has elapsed for condition: x == y after 2ms")]
+    async fn it_times_out_after_integer_milliseconds() {
+        let x = 1;
+        let y = 2;
+
+        deadline!(2, move || x == y);
+    }
+
+    #[tokio::test]
+    async fn it_takes_the_wait_limit_by_reference() {
+        let wait_limit = Duration::from_millis(10);
+        let x = 42;
+
+        deadline!(&wait_limit, move || x == 42);
+        try_deadline!(&wait_limit, move || x == 42).unwrap();
+        deadline_eq!(&wait_limit, move || x, 42);
+        deadline_sustained!(&wait_limit, move || x == 42);
+        deadline_settle!(&wait_limit, &Duration::from_millis(1), move || x == 42);
+        deadline_never!(&wait_limit, move || x != 42);
+        assert_eq!(wait_limit, Duration::from_millis(10));
+    }
+
+    #[tokio::test]
+    #[should_panic(expected = "the deadline has elapsed for condition: x == y after 3ms")]
+    async fn it_converts_the_wait_limit_into_a_duration() {
+        struct Millis(u64);
+
+        impl From<Millis> for Duration {
+            fn from(millis: Millis) -> Self {
+                Duration::from_millis(millis.0)
+            }
+        }
+
+        let x = 1;
+        let y = 2;
+
+        deadline_future(Millis(10), move || x == 1).await.unwrap();
+        deadline_sustained!(Millis(1), move || x == 1);
+        deadline!(Millis(3), move || x == y);
+    }
+
+    #[tokio::test]
+    #[should_panic(expected = "x should reach 2: the deadline has elapsed for condition: x == y")]
+    async fn it_prefixes_the_custom_message() {
+        let x = 1;
+        let y = 2;
+
+        deadline!(
+            Duration::from_millis(1),
+            move || x == y,
+            "x should reach {}",
+            y
+        );
+    }
+
+    #[tokio::test]
+    async fn it_adapts_the_interval_to_progress() {
+        let sleeper = MockSleeper::new();
+        let values = RefCell::new(vec![0, 0, 0, 1, 2, 3].into_iter());
+
+        let result = deadline_progress_inner(
+            &sleeper,
+            Duration::from_secs(1),
+            || values.borrow_mut().next().unwrap(),
+            |value| value == 3,
+        )
+        .await;
+
+        assert_eq!(result.ok(), Some(3));
+        // Doubles while stalled, then halves while moving.
+        assert_eq!(sleeper.sleeps(), millis(&[1, 2, 4, 2, 1]));
+    }
+
+    #[tokio::test]
+    #[should_panic(expected = "condition: |value| value > 5 on 2 (last = 2, polls = ")]
+    async fn it_reports_the_last_progress_value() {
+        deadline_progress!(Duration::from_millis(1), || 2, |value| value > 5);
+    }
+
+    #[tokio::test]
+    async fn it_reports_where_the_value_went_backwards() {
+        let sleeper = MockSleeper::new();
+        let values = RefCell::new(vec![1, 2, 2, 1, 3].into_iter());
+
+        let result = deadline_monotonic_inner(
+            &sleeper,
+            Duration::from_secs(1),
+            || values.borrow_mut().next().unwrap(),
+            &3,
+        )
+        .await;
+
+        assert_eq!(
+            result,
+            Err(NotMonotonic::Regressed {
+                previous: 2,
+                value: 1,
+                poll: 4,
+                after: Duration::from_millis(3),
+            })
+        );
+    }
+
+    #[tokio::test]
+    #[should_panic(expected = "the deadline has elapsed for 2 to reach 5 (last = 2, polls = ")]
+    async fn it_reports_the_last_monotonic_value() {
+        deadline_monotonic!(Duration::from_millis(1), || 2, 5);
+    }
+
+    #[tokio::test]
+    async fn it_evaluates_to_the_some_value() {
+        let calls = Cell::new(0);
+
+        let x = deadline_some!(Duration::from_secs(1), || {
+            calls.set(calls.get() + 1);
+            (calls.get() == 3).then_some(42)
+        });
+
+        assert_eq!(x, 42);
+    }
+
+    #[tokio::test]
+    #[should_panic(expected = "(last error = \"not yet\") after 1ms")]
+    async fn it_reports_the_last_error() {
+        let _: () = deadline_ok!(Duration::from_millis(1), || Err("not yet"));
+    }
+
+    #[cfg(feature = "tokio")]
+    #[tokio::test(start_paused = true)]
+    async fn it_only_evaluates_when_notified() {
+        let x = Arc::new(AtomicI32::new(0));
+        let changed = Arc::new(tokio::sync::Notify::new());
+        let calls = AtomicI32::new(0);
+
+        let (x_clone, changed_clone) = (x.clone(), changed.clone());
+        tokio::spawn(async move {
+            for _ in 0..3 {
+                tokio::time::sleep(Duration::from_secs(1)).await;
+                x_clone.fetch_add(1, Ordering::SeqCst);
+                changed_clone.notify_one();
+            }
+        });
+
+        deadline_notify!(Duration::from_secs(10), changed, || {
+            calls.fetch_add(1, Ordering::SeqCst);
+            x.load(Ordering::Relaxed) == 3
+        });
+
+        // Once upfront, then once per notification.
+        assert_eq!(calls.load(Ordering::SeqCst), 4);
+    }
+
+    #[tokio::test]
+    async fn it_waits_with_each_strategy() {
+        let strategies = [
+            PollStrategy::Spin,
+            PollStrategy::Interval(Duration::from_millis(1)),
+            PollStrategy::Backoff {
+                start: Duration::from_millis(1),
+                cap: Duration::from_millis(4),
+            },
+        ];
+
+        for strategy in strategies {
+            let x = Arc::new(AtomicI32::new(41));
+
+            let x_clone = x.clone();
+            tokio::spawn(async move {
+                tokio::time::sleep(Duration::from_millis(5)).await;
+                x_clone.fetch_add(1, Ordering::SeqCst);
+            });
+
+            deadline_strategy!(Duration::from_secs(1), strategy, move || {
+                x.load(Ordering::Relaxed) == 42
+            });
+        }
+    }
+
+    #[tokio::test]
+    #[should_panic(expected = "not spun up: the deadline has elapsed for condition: x == y")]
+    async fn it_times_out_with_a_strategy() {
+        let x = 1;
+        let y = 2;
+
+        deadline_strategy!(
+            Duration::from_millis(10),
+            PollStrategy::Spin,
+            move || x == y,
+            "not {} up",
+            "spun"
+        );
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn it_yields_to_a_spawned_task_when_spinning_on_a_current_thread_runtime() {
+        let x = Arc::new(AtomicI32::new(0));
+
+        // Without a timer to wait on, the spawned task only runs when the spinning one yields.
+        let x_clone = x.clone();
+        tokio::spawn(async move {
+            for _ in 0..3 {
+                x_clone.fetch_add(1, Ordering::SeqCst);
+                tokio::task::yield_now().await;
+            }
+        });
+
+        deadline_strategy!(Duration::from_secs(1), PollStrategy::Spin, move || {
+            x.load(Ordering::Relaxed) == 3
+        });
+    }
+
+    #[cfg(feature = "tokio")]
+    #[tokio::test(start_paused = true)]
+    async fn it_evaluates_on_each_change() {
+        let (tx, mut rx) = tokio::sync::watch::channel(0);
+        let calls = AtomicI32::new(0);
+
+        tokio::spawn(async move {
+            for i in 1..=3 {
+                tokio::time::sleep(Duration::from_secs(1)).await;
+                tx.send(i).unwrap();
+            }
+            // Keep the sender alive.
+            std::future::pending::<()>().await;
+        });
+
+        deadline_watch!(Duration::from_secs(10), rx, |value| {
+            calls.fetch_add(1, Ordering::SeqCst);
+            *value == 3
+        });
+
+        // Once upfront, then once per change.
+        assert_eq!(calls.load(Ordering::SeqCst), 4);
+
+        // Already satisfied, so this returns without waiting for a change.
+        let started = tokio::time::Instant::now();
+        deadline_watch!(Duration::from_secs(10), rx, |value| *value == 3);
+        assert_eq!(started.elapsed(), Duration::ZERO);
+    }
+
+    #[cfg(feature = "tokio")]
+    #[tokio::test]
+    #[should_panic(
+        expected = "the sender was dropped before condition: |value| *value == 1 held on: rx"
+    )]
+    async fn it_panics_when_the_sender_is_dropped() {
+        let (tx, mut rx) = tokio::sync::watch::channel(0);
+        drop(tx);
+
+        deadline_watch!(Duration::from_secs(1), rx, |value| *value == 1);
+    }
+
+    #[cfg(feature = "tokio")]
+    deadline_test! {
+        Duration::from_secs(1);
+
+        async fn it_runs_the_test_within_its_deadline() {
+            tokio::time::sleep(Duration::from_millis(1)).await;
+        }
+
+        async fn it_returns_the_test_result() -> Result<(), String> {
+            Ok(())
+        }
+
+        #[should_panic(
+            expected = "the test deadline::tests::it_fails_a_test_exceeding_its_deadline exceeded \
+                        its deadline of 1s"
+        )]
+        async fn it_fails_a_test_exceeding_its_deadline() {
+            tokio::time::pause();
+            std::future::pending::<()>().await;
+        }
+    }
+
+    #[tokio::test]
+    async fn it_borrows_locals_in_async_conditions() {
+        let status = Arc::new(tokio::sync::RwLock::new(String::from("starting")));
+        let expected = String::from("ready");
+        let expected: &str = &expected;
+
+        let status_clone = status.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+            *status_clone.write().await = "ready".to_owned();
+        });
+
+        deadline_async!(Duration::from_secs(1), || async {
+            tokio::task::yield_now().await;
+            *status.read().await == expected
+        });
+    }
+
+    #[tokio::test]
+    #[should_panic(
+        expected = "the deadline has elapsed for condition: async move { x == y } after 2ms"
+    )]
+    async fn it_times_out_on_async_conditions() {
+        let x = 1;
+        let y = 2;
+
+        deadline_async!(Duration::from_millis(2), move || async move { x == y });
+    }
+
+    #[cfg(feature = "tokio")]
+    #[tokio::test]
+    async fn it_releases_the_lock_between_evaluations() {
+        let counter = Arc::new(tokio::sync::Mutex::new(0));
+
+        // The task can only make progress while the lock is released.
+        let counter_clone = counter.clone();
+        tokio::spawn(async move {
+            for _ in 0..3 {
+                *counter_clone.lock().await += 1;
+                tokio::time::sleep(Duration::from_millis(1)).await;
+            }
+        });
+
+        deadline_lock!(Duration::from_secs(1), counter, |count| *count == 3);
+
+        let done = tokio::sync::RwLock::new(true);
+        deadline_lock!(Duration::ZERO, done, |done| *done);
+    }
+
+    #[cfg(feature = "tokio")]
+    #[tokio::test]
+    #[should_panic(
+        expected = "the deadline has elapsed for condition: |count| *count > 0 on: \
+                               &counter after 2ms"
+    )]
+    async fn it_times_out_waiting_on_a_lock() {
+        let counter = tokio::sync::Mutex::new(0);
+
+        deadline_lock!(Duration::from_millis(2), &counter, |count| *count > 0);
+    }
+
+    #[cfg(feature = "tokio")]
+    #[tokio::test]
+    async fn it_evaluates_off_the_runtime() {
+        let ticks = Arc::new(AtomicI32::new(0));
+
+        let ticks_clone = ticks.clone();
+        tokio::spawn(async move {
+            loop {
+                tokio::time::sleep(Duration::from_millis(1)).await;
+                ticks_clone.fetch_add(1, Ordering::SeqCst);
+            }
+        });
+
+        // The ticks can only advance during the blocking sleep if the runtime isn't blocked.
+        deadline_spawn_blocking!(Duration::from_secs(5), move || {
+            let before = ticks.load(Ordering::SeqCst);
+            std::thread::sleep(Duration::from_millis(20));
+            ticks.load(Ordering::SeqCst) > before
+        });
+    }
+
+    #[cfg(feature = "tokio")]
+    #[tokio::test]
+    async fn it_times_out_a_first_spawn_blocking_evaluation_that_never_returns() {
+        let released = Arc::new(AtomicBool::new(false));
+
+        let released_clone = released.clone();
+        let started = Instant::now();
+        let result = deadline_spawn_blocking_inner(Duration::from_millis(20), move || {
+            while !released_clone.load(Ordering::SeqCst) {
+                std::thread::sleep(Duration::from_millis(1));
+            }
+            true
+        })
+        .await;
+        let waited = started.elapsed();
+        // Lets the blocking thread finish, as the runtime waits for it when it shuts down.
+        released.store(true, Ordering::SeqCst);
+
+        assert!(matches!(
+            result,
+            Err(DeadlineError::TimedOut { polls: 1, .. })
+        ));
+        assert!(waited < Duration::from_secs(1), "waited {waited:?}");
+    }
+
+    #[tokio::test]
+    async fn it_stops_evaluating_met_conditions() {
+        let sleeper = MockSleeper::new();
+        let (a, b) = (Cell::new(0), Cell::new(0));
+
+        let result = deadline_all_inner(
+            &sleeper,
+            Duration::from_secs(1),
+            (
+                || {
+                    a.set(a.get() + 1);
+                    true
+                },
+                || {
+                    b.set(b.get() + 1);
+                    b.get() == 3
+                },
+            ),
+        )
+        .await;
+
+        assert!(result.is_ok());
+        assert_eq!((a.get(), b.get()), (1, 3));
+    }
+
+    #[tokio::test]
+    #[should_panic(expected = "conditions still false: [\"x == 2\", \"y == 1\"] after 1ms")]
+    async fn it_lists_the_conditions_still_false() {
+        let x = 1;
+        let y = 2;
+
+        deadline_all!(
+            Duration::from_millis(1),
+            [move || x == 1, move || x == 2, move || y == 1]
+        );
+    }
+
+    #[tokio::test]
+    async fn it_evaluates_to_the_index_of_the_first_true_condition() {
+        let x = Arc::new(AtomicI32::new(0));
+
+        let x_clone = x.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+            x_clone.store(2, Ordering::SeqCst);
+        });
+
+        let fired = deadline_any!(
+            Duration::from_secs(1),
+            [
+                || x.load(Ordering::Relaxed) == 1,
+                || x.load(Ordering::Relaxed) == 2,
+            ]
+        );
+
+        assert_eq!(fired, 1);
+    }
+
+    #[tokio::test]
+    #[should_panic(expected = "none of the conditions true: [\"x == 2\", \"x == 3\"] after 1ms")]
+    async fn it_lists_all_the_conditions_when_none_fire() {
+        let x = 1;
+
+        deadline_any!(Duration::from_millis(1), [move || x == 2, move || x == 3]);
+    }
+
+    #[tokio::test]
+    async fn it_waits_for_the_warmup_before_evaluating() {
+        let sleeper = MockSleeper::new();
+        let started = sleeper.now();
+        let first_evaluation = Cell::new(None);
+
+        let result = Deadline::new(Duration::from_millis(10))
+            .warmup(Duration::from_millis(5))
+            .sleeper(&sleeper)
+            .wait(|| {
+                let now = sleeper.now().saturating_duration_since(started);
+                first_evaluation.set(first_evaluation.get().or(Some(now)));
+                false
+            })
+            .await;
+
+        assert_eq!(first_evaluation.get(), Some(Duration::from_millis(5)));
+        // The warmup counts against the wait limit.
+        assert_eq!(result.unwrap_err().waited(), Duration::from_millis(10));
+        assert_eq!(sleeper.sleeps(), millis(&[5, 1, 1, 1, 1, 1]));
+    }
+
+    #[tokio::test]
+    async fn it_polls_until_the_timeout_completes() {
+        let calls = Cell::new(0);
+
+        let result = with_timeout(tokio::task::yield_now(), || {
+            calls.set(calls.get() + 1);
+            false
+        })
+        .await;
+
+        // Evaluated before and after the timeout's own yield.
+        assert_eq!(result.map_err(|timed_out| timed_out.polls()), Err(2));
+        assert_eq!(calls.get(), 2);
     }
 }
@@ -0,0 +1,36 @@
+//! The async locks `deadline_lock!` evaluates its predicate through.
+
+use core::future::Future;
+
+/// A lock whose value can be evaluated once locked, for
+/// [`deadline_lock!`](crate::deadline_lock).
+///
+/// The guard lives for the duration of the evaluation only, so the lock is released before the
+/// macro sleeps.
+#[doc(hidden)]
+pub trait AsyncLock {
+    type Target: ?Sized;
+
+    fn with_guard<R>(&self, f: impl FnOnce(&Self::Target) -> R) -> impl Future<Output = R>;
+
+    /// Returns the predicate as is, so a closure's argument type can be inferred from the lock.
+    fn predicate<P: Fn(&Self::Target) -> bool>(&self, predicate: P) -> P {
+        predicate
+    }
+}
+
+impl<T: ?Sized> AsyncLock for tokio::sync::Mutex<T> {
+    type Target = T;
+
+    async fn with_guard<R>(&self, f: impl FnOnce(&T) -> R) -> R {
+        f(&*self.lock().await)
+    }
+}
+
+impl<T: ?Sized> AsyncLock for tokio::sync::RwLock<T> {
+    type Target = T;
+
+    async fn with_guard<R>(&self, f: impl FnOnce(&T) -> R) -> R {
+        f(&*self.read().await)
+    }
+}
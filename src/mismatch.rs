@@ -0,0 +1,95 @@
+//! How `deadline_eq!` renders the last value alongside the expected one on timeout.
+
+use core::fmt::Debug;
+
+/// The parts of a `deadline_eq!` panic message describing how the values differ.
+#[doc(hidden)]
+#[derive(Debug)]
+pub struct Mismatch {
+    /// Follows the condition, e.g. ` (left = 1, right = 2)`.
+    pub inline: String,
+    /// Ends the message, e.g. with a diff of the values spanning several lines.
+    pub trailing: String,
+}
+
+/// Renders the values side by side, with their `Debug` implementation.
+#[doc(hidden)]
+#[cfg(not(feature = "diff"))]
+pub fn mismatch<L: Debug, R: Debug>(left: &L, right: &R) -> Mismatch {
+    Mismatch {
+        inline: format!(" (left = {left:?}, right = {right:?})"),
+        trailing: String::new(),
+    }
+}
+
+/// Renders a line diff of the values' pretty-printed `Debug` representations.
+#[doc(hidden)]
+#[cfg(feature = "diff")]
+pub fn mismatch<L: Debug, R: Debug>(left: &L, right: &R) -> Mismatch {
+    use core::fmt::Write;
+
+    use similar::{ChangeTag, TextDiff};
+
+    let (left, right) = (format!("{left:#?}"), format!("{right:#?}"));
+    let mut trailing = String::from("\n\ndiff < left / right >:\n");
+    for change in TextDiff::from_lines(&left, &right).iter_all_changes() {
+        let sign = match change.tag() {
+            ChangeTag::Delete => '<',
+            ChangeTag::Insert => '>',
+            ChangeTag::Equal => ' ',
+        };
+        // The last line of each side has no line break of its own.
+        let _ = write!(trailing, "{sign}{}", change.value());
+        if change.missing_newline() {
+            trailing.push('\n');
+        }
+    }
+
+    Mismatch {
+        inline: String::new(),
+        trailing,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug)]
+    #[allow(dead_code)]
+    struct Config {
+        name: &'static str,
+        port: u16,
+    }
+
+    #[test]
+    #[cfg(not(feature = "diff"))]
+    fn it_renders_the_values_inline() {
+        let mismatch = mismatch(&41, &42);
+
+        assert_eq!(mismatch.inline, " (left = 41, right = 42)");
+        assert_eq!(mismatch.trailing, "");
+    }
+
+    #[test]
+    #[cfg(feature = "diff")]
+    fn it_diffs_the_values() {
+        let left = Config {
+            name: "server",
+            port: 8080,
+        };
+        let right = Config {
+            name: "server",
+            port: 8081,
+        };
+
+        let mismatch = mismatch(&left, &right);
+
+        assert_eq!(mismatch.inline, "");
+        assert_eq!(
+            mismatch.trailing,
+            "\n\ndiff < left / right >:\n Config {\n     name: \"server\",\n<    port: 8080,\n>    \
+             port: 8081,\n }\n"
+        );
+    }
+}
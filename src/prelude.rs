@@ -0,0 +1,52 @@
+//! The crate's commonly used items, to be glob imported.
+//!
+//! ```rust
+//! use deadline::prelude::*;
+//! ```
+//!
+//! The macros are exported at the crate root by `#[macro_export]`, and re-exported here so that
+//! the glob import brings them into scope along with the types they work with. Either path refers
+//! to the same macro, so `deadline::deadline!` keeps working and importing both is harmless.
+//!
+//! # Examples
+//!
+//! ```rust
+//! # #[tokio::main]
+//! # async fn main() {
+//! #     use std::time::Duration;
+//! #
+//! use deadline::prelude::*;
+//!
+//! let x = 42;
+//!
+//! deadline!(Duration::from_millis(10), move || x == 42);
+//!
+//! let outcome: DeadlineOutcome = Deadline::new(Duration::from_millis(10))
+//!     .wait(move || x == 42)
+//!     .await
+//!     .unwrap();
+//! assert_eq!(outcome.polls, 1);
+//! # }
+//! ```
+
+#[cfg(feature = "tokio-util")]
+pub use crate::deadline_cancellable;
+pub use crate::{
+    deadline, deadline_all, deadline_any, deadline_approx, deadline_async, deadline_atomic,
+    deadline_backoff, deadline_blocking, deadline_blocking_bool, deadline_budget, deadline_ctx,
+    deadline_default, deadline_eq, deadline_eq_fn, deadline_future, deadline_ge, deadline_gt,
+    deadline_le, deadline_len, deadline_lt, deadline_monotonic, deadline_ms, deadline_mut,
+    deadline_never, deadline_ok, deadline_poll_fn, deadline_polls, deadline_progress, deadline_q,
+    deadline_recv, deadline_retry, deadline_retry_panics, deadline_secs, deadline_settle,
+    deadline_some, deadline_stages, deadline_strategy, deadline_sustained, deadline_then,
+    deadline_timed, deadline_try, deadline_until, deadline_value, deadline_warn,
+    deadline_with_interval, default_poll_strategy, default_wait_limit, normalize_condition,
+    panic_with_failures, set_debug_scale, set_default_wait_limit, set_defaults,
+    set_failure_formatter, try_deadline, with_timeout, Budget, Deadline, DeadlineError,
+    DeadlineFailure, DeadlineOutcome, Defaults, IsReady, PollStrategy, RuntimeSleeper, Sleeper,
+    SpinSleeper, TimedOut,
+};
+#[cfg(feature = "tokio")]
+pub use crate::{
+    deadline_lock, deadline_notify, deadline_spawn_blocking, deadline_test, deadline_watch,
+};
@@ -0,0 +1,72 @@
+//! What a condition can return to say whether it's met.
+
+/// A value saying whether a condition is met, which the conditions of
+/// [`deadline!`](crate::deadline) can return instead of a `bool`.
+///
+/// This goes for the macros polling a condition until it's met and little else, such as
+/// [`try_deadline!`](crate::try_deadline), [`deadline_backoff!`](crate::deadline_backoff) or
+/// [`deadline_blocking!`](crate::deadline_blocking), and for
+/// [`Deadline::wait`](crate::Deadline::wait). Those checking a condition in other ways, like
+/// [`deadline_sustained!`](crate::deadline_sustained), still take a `bool`.
+///
+/// `true`, `Some` and `Ok` mean the condition is met, `false`, `None` and `Err` that it's worth
+/// waiting for. Implementing this for a readiness type of your own saves mapping it to a `bool` in
+/// every condition. To get hold of the value an `Option` or `Result` holds once it's met, see
+/// [`deadline_some!`](crate::deadline_some) and [`deadline_ok!`](crate::deadline_ok).
+///
+/// # Examples
+///
+/// ```rust
+/// # #[tokio::main]
+/// # async fn main() {
+/// #     use std::{
+/// #         sync::{Arc, Mutex},
+/// #         time::Duration,
+/// #     };
+/// #
+/// #     use deadline::{deadline, IsReady};
+/// #[derive(Clone, Copy, PartialEq)]
+/// enum Ready {
+///     Yes,
+///     No,
+/// }
+///
+/// impl IsReady for Ready {
+///     fn is_ready(&self) -> bool {
+///         *self == Ready::Yes
+///     }
+/// }
+///
+/// let ready = Arc::new(Mutex::new(Ready::No));
+///
+/// let ready_clone = ready.clone();
+/// tokio::spawn(async move {
+///     tokio::time::sleep(Duration::from_millis(5)).await;
+///     *ready_clone.lock().unwrap() = Ready::Yes;
+/// });
+///
+/// deadline!(Duration::from_millis(100), move || *ready.lock().unwrap());
+/// # }
+/// ```
+pub trait IsReady {
+    /// Returns whether the condition is met.
+    fn is_ready(&self) -> bool;
+}
+
+impl IsReady for bool {
+    fn is_ready(&self) -> bool {
+        *self
+    }
+}
+
+impl<T> IsReady for Option<T> {
+    fn is_ready(&self) -> bool {
+        self.is_some()
+    }
+}
+
+impl<T, E> IsReady for Result<T, E> {
+    fn is_ready(&self) -> bool {
+        self.is_ok()
+    }
+}
@@ -0,0 +1,343 @@
+//! The async runtime backing the crate's timers, selected through cargo features.
+
+#[cfg(any(
+    all(feature = "tokio", feature = "async-std"),
+    all(feature = "tokio", feature = "smol"),
+    all(feature = "tokio", feature = "wasm"),
+    all(feature = "tokio", feature = "glommio"),
+    all(feature = "async-std", feature = "smol"),
+    all(feature = "async-std", feature = "wasm"),
+    all(feature = "async-std", feature = "glommio"),
+    all(feature = "smol", feature = "wasm"),
+    all(feature = "smol", feature = "glommio"),
+    all(feature = "wasm", feature = "glommio"),
+    all(feature = "minimal", feature = "tokio"),
+    all(feature = "minimal", feature = "async-std"),
+    all(feature = "minimal", feature = "smol"),
+    all(feature = "minimal", feature = "wasm"),
+    all(feature = "minimal", feature = "glommio"),
+))]
+// Only the first enabled backend in `tokio`, `async-std`, `smol`, `wasm`, `glommio`, `minimal`
+// order is compiled so that this is the only error reported.
+compile_error!(
+    "only one runtime backend can be enabled at a time, pick one of the `tokio`, `async-std`, \
+     `smol`, `wasm`, `glommio` or `minimal` features (`tokio` is enabled by default, use \
+     `default-features = false` to opt out)"
+);
+
+#[cfg(not(any(
+    feature = "tokio",
+    feature = "async-std",
+    feature = "smol",
+    feature = "wasm",
+    feature = "glommio",
+    feature = "minimal"
+)))]
+compile_error!(
+    "the `std` feature requires a runtime backend, enable one of the `tokio`, `async-std`, `smol`, \
+     `wasm`, `glommio` or `minimal` features, or disable `std` to only use `with_timeout`"
+);
+
+#[cfg(feature = "tokio")]
+pub use self::tokio_rt::*;
+
+#[cfg(all(feature = "async-std", not(feature = "tokio")))]
+pub use self::async_std_rt::*;
+
+#[cfg(all(feature = "smol", not(any(feature = "tokio", feature = "async-std"))))]
+pub use self::smol_rt::*;
+
+#[cfg(all(
+    feature = "wasm",
+    not(any(feature = "tokio", feature = "async-std", feature = "smol"))
+))]
+pub use self::wasm_rt::*;
+
+#[cfg(all(
+    feature = "glommio",
+    not(any(
+        feature = "tokio",
+        feature = "async-std",
+        feature = "smol",
+        feature = "wasm"
+    ))
+))]
+pub use self::glommio_rt::*;
+
+#[cfg(all(
+    feature = "minimal",
+    not(any(
+        feature = "tokio",
+        feature = "async-std",
+        feature = "smol",
+        feature = "wasm",
+        feature = "glommio"
+    ))
+))]
+pub use self::minimal_rt::*;
+
+#[cfg(feature = "tokio")]
+mod tokio_rt {
+    use core::{future::Future, time::Duration};
+
+    pub use tokio::time::Instant;
+
+    use crate::DeadlineError;
+
+    pub(crate) fn now() -> std::time::Instant {
+        Instant::now().into_std()
+    }
+
+    pub(crate) async fn sleep(duration: Duration) {
+        tokio::time::sleep(duration).await;
+    }
+
+    pub(crate) async fn yield_now() {
+        tokio::task::yield_now().await;
+    }
+
+    pub(crate) async fn timeout<F: Future>(
+        duration: Duration,
+        future: F,
+    ) -> Result<F::Output, DeadlineError> {
+        let started = now();
+        tokio::time::timeout(duration, future)
+            .await
+            .map_err(|_| DeadlineError::timed_out(duration, now() - started, 1))
+    }
+}
+
+#[cfg(all(feature = "async-std", not(feature = "tokio")))]
+mod async_std_rt {
+    use core::{future::Future, time::Duration};
+
+    pub use std::time::Instant;
+
+    use crate::DeadlineError;
+
+    pub(crate) fn now() -> Instant {
+        Instant::now()
+    }
+
+    pub(crate) async fn sleep(duration: Duration) {
+        async_std::task::sleep(duration).await;
+    }
+
+    pub(crate) async fn yield_now() {
+        async_std::task::yield_now().await;
+    }
+
+    pub(crate) async fn timeout<F: Future>(
+        duration: Duration,
+        future: F,
+    ) -> Result<F::Output, DeadlineError> {
+        let started = now();
+        async_std::future::timeout(duration, future)
+            .await
+            .map_err(|_| DeadlineError::timed_out(duration, now() - started, 1))
+    }
+}
+
+#[cfg(all(feature = "smol", not(any(feature = "tokio", feature = "async-std"))))]
+mod smol_rt {
+    use core::{future::Future, time::Duration};
+
+    pub use std::time::Instant;
+
+    use crate::DeadlineError;
+
+    pub(crate) fn now() -> Instant {
+        Instant::now()
+    }
+
+    pub(crate) async fn sleep(duration: Duration) {
+        smol::Timer::after(duration).await;
+    }
+
+    pub(crate) async fn yield_now() {
+        smol::future::yield_now().await;
+    }
+
+    pub(crate) async fn timeout<F: Future>(
+        duration: Duration,
+        future: F,
+    ) -> Result<F::Output, DeadlineError> {
+        let started = now();
+        smol::future::or(async { Ok(future.await) }, async {
+            sleep(duration).await;
+            Err(DeadlineError::timed_out(duration, now() - started, 1))
+        })
+        .await
+    }
+}
+
+#[cfg(all(
+    feature = "wasm",
+    not(any(feature = "tokio", feature = "async-std", feature = "smol"))
+))]
+mod wasm_rt {
+    use core::{future::Future, pin::pin, task::Poll, time::Duration};
+
+    pub use web_time::Instant;
+
+    use crate::DeadlineError;
+
+    /// The longest delay `setTimeout` supports, as longer ones fire immediately.
+    const MAX_TIMEOUT_MILLIS: u32 = i32::MAX as u32;
+
+    pub(crate) fn now() -> Instant {
+        Instant::now()
+    }
+
+    pub(crate) async fn sleep(duration: Duration) {
+        let millis = u32::try_from(duration.as_millis()).unwrap_or(u32::MAX);
+        gloo_timers::future::TimeoutFuture::new(millis.min(MAX_TIMEOUT_MILLIS)).await;
+    }
+
+    /// Waits for a zero delay timeout, as waking the task straight away would starve the browser's
+    /// event loop.
+    pub(crate) async fn yield_now() {
+        sleep(Duration::ZERO).await;
+    }
+
+    pub(crate) async fn timeout<F: Future>(
+        duration: Duration,
+        future: F,
+    ) -> Result<F::Output, DeadlineError> {
+        let started = now();
+        let mut future = pin!(future);
+        let mut sleep = pin!(sleep(duration));
+
+        core::future::poll_fn(|cx| {
+            if let Poll::Ready(output) = future.as_mut().poll(cx) {
+                return Poll::Ready(Ok(output));
+            }
+            sleep
+                .as_mut()
+                .poll(cx)
+                .map(|()| Err(DeadlineError::timed_out(duration, now() - started, 1)))
+        })
+        .await
+    }
+}
+
+#[cfg(all(
+    feature = "glommio",
+    not(any(
+        feature = "tokio",
+        feature = "async-std",
+        feature = "smol",
+        feature = "wasm"
+    ))
+))]
+mod glommio_rt {
+    use core::{future::Future, time::Duration};
+
+    pub use std::time::Instant;
+
+    use crate::DeadlineError;
+
+    /// The longest timer set at once, as glommio panics on deadlines overflowing an `Instant`.
+    const MAX_TIMER: Duration = Duration::from_secs(60 * 60 * 24 * 365);
+
+    pub(crate) fn now() -> Instant {
+        Instant::now()
+    }
+
+    pub(crate) async fn sleep(duration: Duration) {
+        glommio::timer::sleep(duration.min(MAX_TIMER)).await;
+    }
+
+    pub(crate) async fn yield_now() {
+        glommio::executor().yield_now().await;
+    }
+
+    pub(crate) async fn timeout<F: Future>(
+        duration: Duration,
+        future: F,
+    ) -> Result<F::Output, DeadlineError> {
+        let started = now();
+        glommio::timer::timeout(duration.min(MAX_TIMER), async { Ok(future.await) })
+            .await
+            .map_err(|_| DeadlineError::timed_out(duration, now() - started, 1))
+    }
+}
+
+#[cfg(all(
+    feature = "minimal",
+    not(any(
+        feature = "tokio",
+        feature = "async-std",
+        feature = "smol",
+        feature = "wasm",
+        feature = "glommio"
+    ))
+))]
+mod minimal_rt {
+    use core::{
+        future::{poll_fn, Future},
+        pin::pin,
+        task::Poll,
+        time::Duration,
+    };
+
+    pub use std::time::Instant;
+
+    use crate::DeadlineError;
+
+    pub(crate) fn now() -> Instant {
+        Instant::now()
+    }
+
+    /// Yields to the executor until `duration` has elapsed, as there's no timer to wait on.
+    pub(crate) async fn sleep(duration: Duration) {
+        let started = Instant::now();
+        let deadline = started.checked_add(duration);
+
+        poll_fn(|cx| {
+            if deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+                return Poll::Ready(());
+            }
+            cx.waker().wake_by_ref();
+            Poll::Pending
+        })
+        .await
+    }
+
+    pub(crate) async fn yield_now() {
+        let mut yielded = false;
+
+        poll_fn(|cx| {
+            if yielded {
+                return Poll::Ready(());
+            }
+            yielded = true;
+            cx.waker().wake_by_ref();
+            Poll::Pending
+        })
+        .await
+    }
+
+    /// Checks the deadline each time the future is polled, yielding to the executor in between.
+    pub(crate) async fn timeout<F: Future>(
+        duration: Duration,
+        future: F,
+    ) -> Result<F::Output, DeadlineError> {
+        let mut future = pin!(future);
+        let started = Instant::now();
+        let deadline = started.checked_add(duration);
+
+        poll_fn(|cx| {
+            if let Poll::Ready(output) = future.as_mut().poll(cx) {
+                return Poll::Ready(Ok(output));
+            }
+            let now = Instant::now();
+            if deadline.is_some_and(|deadline| now >= deadline) {
+                return Poll::Ready(Err(DeadlineError::timed_out(duration, now - started, 1)));
+            }
+            cx.waker().wake_by_ref();
+            Poll::Pending
+        })
+        .await
+    }
+}
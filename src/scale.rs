@@ -0,0 +1,124 @@
+//! Scaling of wait limits through the `DEADLINE_SCALE` environment variable, and in debug builds.
+
+use core::{
+    sync::atomic::{AtomicU64, Ordering},
+    time::Duration,
+};
+use std::sync::OnceLock;
+
+/// The factor set through [`set_debug_scale`], as the bits of an `f64`, or zero if unset.
+static DEBUG_SCALE: AtomicU64 = AtomicU64::new(0);
+
+/// Multiplies the wait limit by the factor set in `DEADLINE_SCALE`, if any, and by the
+/// [`set_debug_scale`] factor in debug builds.
+pub(crate) fn scale(wait_limit: Duration) -> Duration {
+    apply_scale(wait_limit, factor())
+}
+
+/// Divides a scaled time back into a wait limit, which [`scale`] turns into that time again.
+pub(crate) fn unscale(scaled: Duration) -> Duration {
+    apply_scale(scaled, factor().recip())
+}
+
+/// Returns the factor wait limits are multiplied by.
+fn factor() -> f64 {
+    static SCALE: OnceLock<f64> = OnceLock::new();
+
+    let scale = *SCALE.get_or_init(|| parse_scale(std::env::var("DEADLINE_SCALE").ok().as_deref()));
+    let debug_scale = debug_scale(DEBUG_SCALE.load(Ordering::Relaxed), cfg!(debug_assertions));
+    scale * debug_scale
+}
+
+/// Sets a factor multiplying the wait limit of every macro in debug builds, which run slower than
+/// the release builds the limits may have been tuned on.
+///
+/// The factor applies when the crate is built with `debug_assertions`, as `cargo test` does by
+/// default, and is ignored otherwise, so `cargo test --release` keeps the limits as written. It
+/// defaults to `1.0`, leaving the limits unchanged. It scales the same wait limits as
+/// `DEADLINE_SCALE`, and combines with it: with `DEADLINE_SCALE=2` and a debug scale of `3`, wait
+/// limits are six times as long in debug builds. Factors that aren't positive numbers are ignored
+/// with a warning.
+///
+/// The setting is global to the process, so it's best called once, before the tests using it
+/// run.
+pub fn set_debug_scale(factor: f64) {
+    if !valid_scale(factor) {
+        eprintln!("deadline: ignoring invalid debug scale {factor}, expected a positive number");
+        return;
+    }
+    DEBUG_SCALE.store(factor.to_bits(), Ordering::Relaxed);
+}
+
+fn debug_scale(bits: u64, debug_assertions: bool) -> f64 {
+    match bits {
+        _ if !debug_assertions => 1.0,
+        0 => 1.0,
+        bits => f64::from_bits(bits),
+    }
+}
+
+fn valid_scale(scale: f64) -> bool {
+    scale.is_finite() && scale > 0.0
+}
+
+fn parse_scale(value: Option<&str>) -> f64 {
+    let Some(value) = value else {
+        return 1.0;
+    };
+
+    match value.trim().parse::<f64>() {
+        Ok(scale) if valid_scale(scale) => scale,
+        _ => {
+            eprintln!("deadline: ignoring invalid DEADLINE_SCALE value {value:?}, expected a positive number");
+            1.0
+        }
+    }
+}
+
+fn apply_scale(wait_limit: Duration, scale: f64) -> Duration {
+    if scale == 1.0 {
+        return wait_limit;
+    }
+
+    Duration::try_from_secs_f64(wait_limit.as_secs_f64() * scale).unwrap_or(Duration::MAX)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_defaults_to_no_scaling() {
+        assert_eq!(parse_scale(None), 1.0);
+    }
+
+    #[test]
+    fn it_parses_the_scale() {
+        assert_eq!(parse_scale(Some("5")), 5.0);
+        assert_eq!(parse_scale(Some(" 0.5 ")), 0.5);
+    }
+
+    #[test]
+    fn it_ignores_invalid_scales() {
+        assert_eq!(parse_scale(Some("fast")), 1.0);
+        assert_eq!(parse_scale(Some("-2")), 1.0);
+        assert_eq!(parse_scale(Some("0")), 1.0);
+        assert_eq!(parse_scale(Some("inf")), 1.0);
+    }
+
+    #[test]
+    fn it_scales_the_wait_limit() {
+        assert_eq!(
+            apply_scale(Duration::from_millis(10), 5.0),
+            Duration::from_millis(50)
+        );
+        assert_eq!(apply_scale(Duration::MAX, 2.0), Duration::MAX);
+    }
+
+    #[test]
+    fn it_scales_debug_builds_only() {
+        assert_eq!(debug_scale(0, true), 1.0);
+        assert_eq!(debug_scale(3.0f64.to_bits(), true), 3.0);
+        assert_eq!(debug_scale(3.0f64.to_bits(), false), 1.0);
+    }
+}
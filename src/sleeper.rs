@@ -0,0 +1,235 @@
+//! The clock the polling loops sleep on between evaluations of a condition.
+
+use core::{
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll},
+    time::Duration,
+};
+#[cfg(not(feature = "wasm"))]
+use std::time::Instant;
+
+#[cfg(feature = "wasm")]
+use web_time::Instant;
+
+use crate::rt;
+
+/// A source of time for the polling loops.
+///
+/// The loops read the current time through [`now`](Sleeper::now) to know when the deadline has
+/// elapsed, and wait between evaluations through [`sleep`](Sleeper::sleep). Swapping the default
+/// [`RuntimeSleeper`] for an implementation advancing a fake clock makes the timing behaviour
+/// deterministic, e.g. to check the intervals requested by a polling strategy.
+pub trait Sleeper {
+    /// Returns the current time.
+    fn now(&self) -> Instant;
+
+    /// Waits for `duration`.
+    fn sleep(&self, duration: Duration) -> impl Future<Output = ()>;
+}
+
+/// The [`Sleeper`] backed by the timers of the runtime selected through cargo features.
+///
+/// A zero duration sleep yields to the runtime the way it recommends, e.g. through
+/// `tokio::task::yield_now`, which lets the runtime's drivers and other tasks make progress before
+/// the task is polled again. With the `wasm` backend, it waits for a zero delay timeout instead.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RuntimeSleeper;
+
+impl Sleeper for RuntimeSleeper {
+    fn now(&self) -> Instant {
+        rt::now()
+    }
+
+    async fn sleep(&self, duration: Duration) {
+        if duration.is_zero() {
+            rt::yield_now().await
+        } else {
+            rt::sleep(duration).await
+        }
+    }
+}
+
+/// Like [`RuntimeSleeper`], but a zero duration sleep wakes the task straight away instead of
+/// yielding to the runtime.
+///
+/// This spins on the condition with the lowest latency a runtime allows, at the cost of keeping the
+/// task runnable at all times, which can starve the tasks supposed to satisfy the condition on some
+/// runtimes. It's only useful along with a zero poll interval.
+///
+/// On a current-thread tokio runtime, where the spinning task would compete for the only thread
+/// with the tasks supposed to satisfy the condition, a zero duration sleep yields to the runtime
+/// like [`RuntimeSleeper`]'s instead.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SpinSleeper;
+
+impl Sleeper for SpinSleeper {
+    fn now(&self) -> Instant {
+        rt::now()
+    }
+
+    async fn sleep(&self, duration: Duration) {
+        if !duration.is_zero() {
+            rt::sleep(duration).await
+        } else if on_current_thread_runtime() {
+            rt::yield_now().await
+        } else {
+            YieldNow(false).await
+        }
+    }
+}
+
+/// Returns whether the task runs on a current-thread tokio runtime.
+fn on_current_thread_runtime() -> bool {
+    #[cfg(feature = "tokio")]
+    {
+        use tokio::runtime::{Handle, RuntimeFlavor};
+
+        Handle::try_current()
+            .is_ok_and(|handle| handle.runtime_flavor() == RuntimeFlavor::CurrentThread)
+    }
+    #[cfg(not(feature = "tokio"))]
+    {
+        false
+    }
+}
+
+impl<S: Sleeper + ?Sized> Sleeper for &S {
+    fn now(&self) -> Instant {
+        (**self).now()
+    }
+
+    fn sleep(&self, duration: Duration) -> impl Future<Output = ()> {
+        (**self).sleep(duration)
+    }
+}
+
+/// Sleeps until the next tick of a schedule aligned on `anchor + n * period`, or for the requested
+/// duration at most.
+///
+/// Ticks that have already passed when sleeping, e.g. during a slow evaluation of the condition,
+/// are skipped rather than caught up on.
+pub(crate) struct AlignedSleeper<S> {
+    sleeper: S,
+    anchor: Instant,
+    period: Duration,
+}
+
+impl<S: Sleeper> AlignedSleeper<S> {
+    pub(crate) fn new(sleeper: S, anchor: Instant, period: Duration) -> Self {
+        Self {
+            sleeper,
+            anchor,
+            period,
+        }
+    }
+}
+
+impl<S: Sleeper> Sleeper for AlignedSleeper<S> {
+    fn now(&self) -> Instant {
+        self.sleeper.now()
+    }
+
+    async fn sleep(&self, duration: Duration) {
+        let now = self.sleeper.now();
+        let until_tick = until_next_tick(self.anchor, self.period, now);
+        self.sleeper.sleep(until_tick.min(duration)).await;
+    }
+}
+
+/// Returns how long until the first tick of `anchor + n * period` strictly after `now`, or until
+/// the anchor itself if it's still ahead.
+fn until_next_tick(anchor: Instant, period: Duration, now: Instant) -> Duration {
+    if anchor > now {
+        return anchor - now;
+    }
+    if period.is_zero() {
+        return Duration::ZERO;
+    }
+
+    let since_anchor = (now - anchor).as_nanos();
+    let period = period.as_nanos();
+    let next_tick = (since_anchor / period + 1) * period;
+    Duration::from_nanos(u64::try_from(next_tick - since_anchor).unwrap_or(u64::MAX))
+}
+
+/// Sleeps until the deadline's [`Notify`](tokio::sync::Notify) is notified, or for the requested
+/// duration at most.
+#[cfg(feature = "tokio")]
+pub(crate) struct NotifySleeper<'a>(pub(crate) &'a tokio::sync::Notify);
+
+#[cfg(feature = "tokio")]
+impl Sleeper for NotifySleeper<'_> {
+    fn now(&self) -> Instant {
+        rt::now()
+    }
+
+    async fn sleep(&self, duration: Duration) {
+        let mut notified = core::pin::pin!(self.0.notified());
+        let mut sleep = core::pin::pin!(rt::sleep(duration));
+
+        core::future::poll_fn(|cx| {
+            if notified.as_mut().poll(cx).is_ready() {
+                return Poll::Ready(());
+            }
+            sleep.as_mut().poll(cx)
+        })
+        .await
+    }
+}
+
+/// Sleeps until the next tick of a [`tokio::time::Interval`], or for the requested duration at
+/// most.
+///
+/// Missed ticks are delayed rather than caught up on, so an evaluation of the condition outlasting
+/// the period doesn't cause a burst of evaluations.
+#[cfg(feature = "tokio")]
+pub(crate) struct IntervalSleeper(std::sync::Mutex<tokio::time::Interval>);
+
+#[cfg(feature = "tokio")]
+impl IntervalSleeper {
+    /// Ticks every `period`, starting one period from now.
+    pub(crate) fn new(period: Duration) -> Self {
+        let mut interval = tokio::time::interval_at(tokio::time::Instant::now() + period, period);
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        Self(std::sync::Mutex::new(interval))
+    }
+}
+
+#[cfg(feature = "tokio")]
+impl Sleeper for IntervalSleeper {
+    fn now(&self) -> Instant {
+        rt::now()
+    }
+
+    async fn sleep(&self, duration: Duration) {
+        let mut sleep = core::pin::pin!(rt::sleep(duration));
+
+        // The lock is only held within each poll, keeping the future `Send`.
+        core::future::poll_fn(|cx| {
+            let tick = self.0.lock().unwrap().poll_tick(cx);
+            if tick.is_ready() {
+                return Poll::Ready(());
+            }
+            sleep.as_mut().poll(cx)
+        })
+        .await
+    }
+}
+
+/// Returns `Pending` once, waking the task straight away.
+struct YieldNow(bool);
+
+impl Future for YieldNow {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        if self.0 {
+            Poll::Ready(())
+        } else {
+            self.0 = true;
+            cx.waker().wake_by_ref();
+            Poll::Pending
+        }
+    }
+}
@@ -0,0 +1,54 @@
+//! How `deadline_strategy!` waits between evaluations of a condition.
+
+use core::time::Duration;
+#[cfg(not(feature = "tokio"))]
+use core::{convert::Infallible, marker::PhantomData};
+
+/// How [`deadline_strategy!`](crate::deadline_strategy) waits between evaluations of its
+/// condition.
+///
+/// Each of the polling macros is a shorthand for one of these: [`deadline_with_interval!`] for
+/// [`Interval`](PollStrategy::Interval), [`deadline_backoff!`] for
+/// [`Backoff`](PollStrategy::Backoff) and [`deadline_notify!`] for
+/// [`Notify`](PollStrategy::Notify). Picking the strategy through a value instead lets it be chosen
+/// at runtime, or shared between the assertions of a test.
+///
+/// [`deadline_with_interval!`]: crate::deadline_with_interval
+/// [`deadline_backoff!`]: crate::deadline_backoff
+/// [`deadline_notify!`]: crate::deadline_notify
+#[derive(Debug, Clone, Copy)]
+#[non_exhaustive]
+pub enum PollStrategy<'a> {
+    /// Re-evaluates the condition each time the task is polled, yielding to the runtime in between
+    /// without sleeping.
+    ///
+    /// This has the lowest latency, at the cost of keeping the task runnable at all times, which
+    /// never lets [paused time](crate#paused-time) advance.
+    ///
+    /// The yield between two evaluations is unconditional, however cheap the condition. Without
+    /// it, the task would never hand the thread back to a single-threaded runtime, such as the
+    /// current-thread flavor `#[tokio::test]` defaults to: the tasks supposed to satisfy the
+    /// condition would never run, and the deadline could only ever time out.
+    Spin,
+    /// Sleeps for the interval between evaluations. A zero interval is the same as
+    /// [`Spin`](PollStrategy::Spin).
+    Interval(Duration),
+    /// Sleeps for `start` after the first evaluation, then doubles the interval after each
+    /// evaluation, up to `cap`.
+    Backoff {
+        /// The interval before the second evaluation.
+        start: Duration,
+        /// The longest interval between evaluations.
+        cap: Duration,
+    },
+    /// Re-evaluates the condition each time the [`Notify`](tokio::sync::Notify) is notified. See
+    /// [`deadline_notify!`](crate::deadline_notify) for how notifications can be missed. Requires
+    /// the `tokio` backend.
+    #[cfg(feature = "tokio")]
+    Notify(&'a tokio::sync::Notify),
+    /// Holds the lifetime the `Notify` variant borrows for, without the `tokio` backend. It can't
+    /// be constructed.
+    #[cfg(not(feature = "tokio"))]
+    #[doc(hidden)]
+    __Unconstructible(Infallible, PhantomData<&'a ()>),
+}
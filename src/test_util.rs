@@ -0,0 +1,77 @@
+//! A switch forcing deadlines to time out, to test the code handling their errors.
+
+use core::{cell::Cell, marker::PhantomData};
+
+// Release builds are what ships, so the switch can't end up in one through a feature enabled in
+// `[dependencies]` by mistake.
+#[cfg(not(debug_assertions))]
+compile_error!(
+    "the `test-util` feature forces deadlines to time out and is only for tests, enable it in \
+     `[dev-dependencies]` and build without `--release`, or set `debug-assertions = true` in the \
+     profile"
+);
+
+thread_local! {
+    static FORCED: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Makes every deadline started on the current thread time out immediately, until the returned
+/// guard is dropped.
+///
+/// This is a testing aid only, for exercising the branches handling a [`DeadlineError`] or a
+/// timeout panic deterministically, without having to wait for a real deadline. The forced
+/// deadlines don't evaluate their condition at all, and report having waited for no time and
+/// evaluated the condition zero times. The macros reporting the last value the condition returned,
+/// like [`deadline_eq!`](crate::deadline_eq), have none to report, so they panic with the message
+/// of [`deadline!`](crate::deadline) instead. [`deadline_sustained!`](crate::deadline_sustained)
+/// and [`deadline_never!`](crate::deadline_never), which pass once their deadline has elapsed,
+/// fail straight away as if the condition broke at once. The switch is per thread, so it doesn't
+/// reach tasks spawned on the worker threads of a multi-threaded runtime.
+///
+/// Requires the `test-util` feature, which is meant to be enabled in `[dev-dependencies]`. Cargo
+/// can't enforce that, so the feature fails to compile without `debug_assertions`, keeping it out
+/// of release builds.
+///
+/// [`DeadlineError`]: crate::DeadlineError
+///
+/// # Examples
+///
+/// ```rust
+/// # #[tokio::main]
+/// # async fn main() {
+/// #     use std::time::Duration;
+/// #
+/// #     use deadline::{test_util, try_deadline};
+/// let _forced = test_util::force_timeout();
+///
+/// // Fails straight away, even though the condition holds.
+/// let error = try_deadline!(Duration::from_secs(60), || true).unwrap_err();
+/// assert_eq!(error.polls(), 0);
+/// # }
+/// ```
+#[must_use = "the timeouts are only forced until the guard is dropped"]
+pub fn force_timeout() -> ForceTimeout {
+    ForceTimeout {
+        previous: FORCED.with(|forced| forced.replace(true)),
+        _thread_bound: PhantomData,
+    }
+}
+
+/// Keeps the deadlines on the current thread timing out until dropped, see [`force_timeout`].
+#[derive(Debug)]
+pub struct ForceTimeout {
+    previous: bool,
+    // The switch is thread-local, so the guard must be dropped on the thread it was created on.
+    _thread_bound: PhantomData<*const ()>,
+}
+
+impl Drop for ForceTimeout {
+    fn drop(&mut self) {
+        FORCED.with(|forced| forced.set(self.previous));
+    }
+}
+
+/// Returns whether the deadlines on the current thread are forced to time out.
+pub(crate) fn forced() -> bool {
+    FORCED.with(Cell::get)
+}
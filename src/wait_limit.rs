@@ -0,0 +1,33 @@
+//! Conversion of the macros' wait limit argument into a `Duration`.
+
+use core::time::Duration;
+
+/// Converts a wait limit passed by value: a `Duration`, or any type implementing
+/// `Into<Duration>`.
+#[doc(hidden)]
+pub trait IntoWaitLimit {
+    fn into_wait_limit(self) -> Duration;
+}
+
+impl<T: Into<Duration>> IntoWaitLimit for T {
+    #[inline(always)]
+    fn into_wait_limit(self) -> Duration {
+        self.into()
+    }
+}
+
+/// Converts a wait limit passed by reference, e.g. a `&Duration` field of a test fixture.
+///
+/// This can't be an impl of [`IntoWaitLimit`] alongside the blanket one, so the macros rely on
+/// method resolution to pick whichever of the two traits applies.
+#[doc(hidden)]
+pub trait RefIntoWaitLimit {
+    fn into_wait_limit(self) -> Duration;
+}
+
+impl<T: Copy + Into<Duration>> RefIntoWaitLimit for &T {
+    #[inline(always)]
+    fn into_wait_limit(self) -> Duration {
+        (*self).into()
+    }
+}
@@ -0,0 +1,68 @@
+//! The timer-agnostic polling core, usable without `std`.
+
+use core::{fmt, future::Future, pin::pin, task::Poll};
+
+/// Error returned by [`with_timeout`] when the timeout completes before the condition is met.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TimedOut {
+    polls: u64,
+}
+
+impl TimedOut {
+    /// Returns how many times the condition was evaluated.
+    pub fn polls(&self) -> u64 {
+        self.polls
+    }
+}
+
+impl fmt::Display for TimedOut {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "deadline has elapsed after {} polls", self.polls)
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for TimedOut {}
+
+/// Evaluates the condition until it returns `true`, or until the `timeout` future completes.
+///
+/// This is the polling core of the crate without any timer attached, so it only needs `core`: the
+/// timeout is any future, e.g. a timer from an embedded executor. The condition is evaluated each
+/// time the returned future is polled, and the task yields to the executor between evaluations, so
+/// the rate of evaluation is up to the executor.
+///
+/// # Examples
+///
+/// ```rust
+/// # #[tokio::main]
+/// # async fn main() {
+/// #     use std::time::Duration;
+/// #
+/// let x = 42;
+///
+/// deadline::with_timeout(tokio::time::sleep(Duration::from_millis(10)), move || x == 42)
+///     .await
+///     .unwrap();
+/// # }
+/// ```
+pub async fn with_timeout<T: Future, F: FnMut() -> bool>(
+    timeout: T,
+    mut condition: F,
+) -> Result<(), TimedOut> {
+    let mut timeout = pin!(timeout);
+    let mut polls = 0;
+
+    core::future::poll_fn(|cx| {
+        polls += 1;
+        if condition() {
+            return Poll::Ready(Ok(()));
+        }
+        if timeout.as_mut().poll(cx).is_ready() {
+            return Poll::Ready(Err(TimedOut { polls }));
+        }
+
+        cx.waker().wake_by_ref();
+        Poll::Pending
+    })
+    .await
+}
@@ -0,0 +1,120 @@
+//! The failure formatter, in a process of its own as it applies to every deadline.
+
+use std::{
+    future::Future,
+    sync::atomic::AtomicUsize,
+    time::{Duration, Instant},
+};
+
+use deadline::*;
+
+const WAIT: Duration = Duration::from_millis(1);
+
+/// Awaits a wait which is expected to time out, returning its panic message.
+async fn panic_message(wait: impl Future<Output = ()> + 'static) -> String {
+    let local = tokio::task::LocalSet::new();
+    let wait = local.spawn_local(wait);
+    let panic = local.run_until(wait).await.unwrap_err().into_panic();
+    *panic.downcast::<String>().unwrap()
+}
+
+#[tokio::test]
+async fn it_formats_the_timeouts_of_every_macro() {
+    set_failure_formatter(|failure| format!("formatted: {}", failure.condition));
+    static ATOMIC: AtomicUsize = AtomicUsize::new(0);
+
+    let messages = [
+        panic_message(async { deadline!(WAIT, || false) }).await,
+        panic_message(async { deadline_retry_panics!(WAIT, || false) }).await,
+        panic_message(async { deadline_budget!(Budget::new(Duration::ZERO), || false) }).await,
+        panic_message(async { deadline_until!(Instant::now(), || false) }).await,
+        panic_message(async { deadline_with_interval!(WAIT, WAIT, || false) }).await,
+        panic_message(async { deadline_backoff!(WAIT, || false) }).await,
+        panic_message(async { deadline_blocking!(WAIT, || false) }).await,
+        panic_message(async { deadline_mut!(WAIT, || false) }).await,
+        panic_message(async { deadline_ctx!(WAIT, &mut 0, |x: &mut i32| *x > 0) }).await,
+        panic_message(async {
+            deadline_timed!(WAIT, || false);
+        })
+        .await,
+        panic_message(async { deadline_some!(WAIT, || None::<()>) }).await,
+        panic_message(async { deadline_ok!(WAIT, || Err::<(), _>("not yet")) }).await,
+        panic_message(async {
+            deadline_try!(WAIT, || core::task::Poll::<Result<(), ()>>::Pending)
+        })
+        .await,
+        panic_message(async { deadline_then!(WAIT, || false, |()| ()) }).await,
+        panic_message(async { deadline_eq!(WAIT, || 1, 2) }).await,
+        panic_message(async { deadline_eq_fn!(WAIT, || 1, || 2) }).await,
+        panic_message(async { deadline_atomic!(WAIT, &ATOMIC, 1) }).await,
+        panic_message(async { deadline_ge!(WAIT, || 1, 2) }).await,
+        panic_message(async { deadline_approx!(WAIT, || 1.0, 2.0, 0.1) }).await,
+        panic_message(async { deadline_len!(WAIT, || 1, >= 2) }).await,
+        panic_message(async {
+            deadline_retry!(WAIT, 2, || false);
+        })
+        .await,
+        panic_message(async {
+            deadline_progress!(WAIT, || 1, |value| value > 1);
+        })
+        .await,
+        panic_message(async {
+            deadline_monotonic!(WAIT, || 1, 2);
+        })
+        .await,
+        panic_message(async { deadline_all!(WAIT, [|| true, || false]) }).await,
+        panic_message(async {
+            deadline_any!(WAIT, [|| false, || false]);
+        })
+        .await,
+        panic_message(async { deadline_stages!([(WAIT, || false)]) }).await,
+        panic_message(async { deadline_sustained!(WAIT * 10, || false) }).await,
+        panic_message(async { deadline_settle!(WAIT, WAIT * 10, || false) }).await,
+        panic_message(async { deadline_never!(WAIT * 10, || true) }).await,
+        panic_message(async { deadline_poll_fn!(WAIT, |_| core::task::Poll::Pending) }).await,
+        panic_message(async { deadline_async!(WAIT, || async { false }) }).await,
+        panic_message(async { deadline_polls!(2, || false) }).await,
+    ];
+    for message in messages {
+        assert!(message.starts_with("formatted: "), "{message}");
+    }
+}
+
+#[cfg(feature = "tokio")]
+#[tokio::test]
+async fn it_formats_the_timeouts_of_the_tokio_macros() {
+    set_failure_formatter(|failure| format!("formatted: {}", failure.condition));
+    static LOCK: tokio::sync::Mutex<bool> = tokio::sync::Mutex::const_new(false);
+    static SENDERS: std::sync::Mutex<Vec<Box<dyn Send>>> = std::sync::Mutex::new(Vec::new());
+
+    let messages = [
+        panic_message(async { deadline_spawn_blocking!(WAIT, || false) }).await,
+        panic_message(async {
+            let notify = tokio::sync::Notify::new();
+            deadline_notify!(WAIT, notify, || false);
+        })
+        .await,
+        panic_message(async {
+            let (tx, mut rx) = tokio::sync::mpsc::channel::<()>(1);
+            SENDERS.lock().unwrap().push(Box::new(tx));
+            deadline_recv!(WAIT, rx);
+        })
+        .await,
+        panic_message(async { deadline_lock!(WAIT, LOCK, |ready| *ready) }).await,
+        panic_message(async {
+            let (tx, mut rx) = tokio::sync::watch::channel(false);
+            SENDERS.lock().unwrap().push(Box::new(tx));
+            deadline_watch!(WAIT, rx, |ready| *ready);
+        })
+        .await,
+        #[cfg(feature = "tokio-util")]
+        panic_message(async {
+            let token = tokio_util::sync::CancellationToken::new();
+            deadline_cancellable!(WAIT, token, || false);
+        })
+        .await,
+    ];
+    for message in messages {
+        assert!(message.starts_with("formatted: "), "{message}");
+    }
+}
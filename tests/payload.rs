@@ -0,0 +1,37 @@
+//! Structured panic payloads, in a process of their own as they apply to every deadline.
+
+use std::{future::Future, time::Duration};
+
+use deadline::*;
+
+const WAIT: Duration = Duration::from_millis(1);
+
+/// Awaits a wait which is expected to time out, returning its panic payload.
+async fn failure(wait: impl Future<Output = ()> + 'static) -> DeadlineFailure<'static> {
+    let local = tokio::task::LocalSet::new();
+    let wait = local.spawn_local(wait);
+    let panic = local.run_until(wait).await.unwrap_err().into_panic();
+    *panic.downcast::<DeadlineFailure<'static>>().unwrap()
+}
+
+#[tokio::test]
+async fn it_panics_with_the_failure_of_every_macro() {
+    panic_with_failures();
+
+    let failures = [
+        failure(async { deadline!(WAIT, || false) }).await,
+        failure(async { deadline_blocking!(WAIT, || false) }).await,
+        failure(async { deadline_eq!(WAIT, || 1, 2) }).await,
+        failure(async { deadline_stages!([(WAIT, || false)]) }).await,
+        failure(async { deadline_sustained!(WAIT * 10, || false) }).await,
+        failure(async { deadline_async!(WAIT, || async { false }) }).await,
+    ];
+    for failure in &failures {
+        assert!(failure.polls >= 1, "{failure:?}");
+    }
+    assert_eq!(failures[0].condition, "false");
+    assert_eq!(failures[2].condition, "1 == 2");
+
+    let polls = failure(async { deadline_polls!(2, || false) }).await;
+    assert_eq!(polls.polls, 2);
+}
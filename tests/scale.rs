@@ -0,0 +1,26 @@
+//! Scaled wait limits, in a process of their own as `DEADLINE_SCALE` applies to every deadline.
+
+use std::time::{Duration, Instant};
+
+use deadline::deadline_until;
+
+#[tokio::test]
+async fn it_times_out_by_the_instant_when_scaled() {
+    // Read once, on first use, which is below.
+    std::env::set_var("DEADLINE_SCALE", "10");
+    let budget = Instant::now() + Duration::from_millis(50);
+
+    let local = tokio::task::LocalSet::new();
+    let wait = local.spawn_local(async move {
+        deadline_until!(budget, || false);
+    });
+    let timed_out = local.run_until(wait).await.is_err();
+
+    assert!(timed_out);
+    // Scaling the remaining time as a wait limit would wait for about 500ms.
+    let late = Instant::now().saturating_duration_since(budget);
+    assert!(
+        late < Duration::from_millis(200),
+        "timed out {late:?} after the instant"
+    );
+}